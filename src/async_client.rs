@@ -0,0 +1,418 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::thread;
+
+use futures::{Future, Sink, Stream};
+use futures::sync::mpsc;
+use futures_cpupool::{CpuFuture, CpuPool};
+
+use super::Client;
+use common::{get_array_stream, LogLine, LogOutput};
+use error::DockerError;
+use containers::{ContainerListOptions, LogOptions};
+use containers::schema::{Container, ContainerCreateConfig, ContainerCreateResponse, ContainerInspect};
+use images::schema::Image;
+use networks::schema::Network;
+use secrets::SECRETS_API_VERSION;
+use secrets::schema::{Secret, SecretSpec};
+use swarm::schema::Swarm;
+use system::schema::{Event, Version};
+use tasks::schema::Task;
+use volumes::schema::Volumes;
+
+pub type DockerFuture<T> = CpuFuture<T, DockerError>;
+pub type DockerStream<T> = Box<Stream<Item = T, Error = DockerError> + Send>;
+
+// Items a stream reads ahead of its consumer
+const STREAM_BUFFER: usize = 16;
+
+// Futures over the blocking `Client`, not a non-blocking client.
+// Requests run on a `CpuPool` and every stream reads on a thread of
+// its own, so the event loop is never blocked but each request in
+// flight holds an OS thread until the daemon has answered.
+#[derive(Clone)]
+pub struct AsyncClient {
+    client: Arc<Client>,
+    pool: CpuPool
+}
+
+macro_rules! async_endpoint {
+    ($sty:ident) => (
+        pub struct $sty {
+            client : AsyncClient
+        }
+
+        impl $sty {
+            pub fn new(client : &AsyncClient) -> $sty {
+                $sty {
+                    client: client.clone()
+                }
+            }
+        }
+    )
+}
+
+impl AsyncClient {
+    pub fn new(client: Client) -> AsyncClient {
+        AsyncClient::with_pool(client, CpuPool::new_num_cpus())
+    }
+
+    pub fn with_pool(client: Client, pool: CpuPool) -> AsyncClient {
+        AsyncClient {
+            client: Arc::new(client),
+            pool: pool
+        }
+    }
+
+    pub fn from_env() -> Result<AsyncClient, DockerError> {
+        Ok(AsyncClient::new(Client::from_env()?))
+    }
+
+    // The blocking client the requests are made with
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn spawn<F, T>(&self, f: F) -> DockerFuture<T>
+        where F: FnOnce(&Client) -> Result<T, DockerError> + Send + 'static,
+              T: Send + 'static
+    {
+        let client = self.client.clone();
+        self.pool.spawn_fn(move || f(&client))
+    }
+
+    // Reads the blocking iterator `f` opens on a thread of its own and
+    // hands every item over as soon as it has been read. Streams like
+    // events can stay open for good, which is why they don't take up
+    // a thread of the pool. Dropping the stream doesn't interrupt the
+    // thread, it stays blocked on the transport until the daemon sends
+    // the next item or ends the response. It then finds the receiver
+    // closed and stops reading, which closes the connection.
+    pub fn spawn_stream<F, I, T>(&self, f: F) -> DockerStream<T>
+        where F: FnOnce(&Client) -> Result<I, DockerError> + Send + 'static,
+              I: Iterator<Item = Result<T, DockerError>>,
+              T: Send + 'static
+    {
+        let client = self.client.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        thread::spawn(move || {
+            let mut sender = sender;
+            let items = match f(&client) {
+                Ok(items) => items,
+                Err(error) => {
+                    let _ = sender.send(Err(error)).wait();
+                    return;
+                }
+            };
+            for item in items {
+                sender = match sender.send(item).wait() {
+                    Ok(sender) => sender,
+                    Err(_) => return
+                };
+            }
+        });
+
+        // the receiver never fails, the items carry the errors
+        Box::new(receiver.then(|item| match item {
+            Ok(item) => item,
+            Err(()) => Err(DockerError::Docker(String::from("stream closed unexpectedly")))
+        }))
+    }
+
+    pub fn images(&self) -> AsyncImagesClient {
+        AsyncImagesClient::new(self)
+    }
+
+    pub fn containers(&self) -> AsyncContainersClient {
+        AsyncContainersClient::new(self)
+    }
+
+    pub fn swarm(&self) -> AsyncSwarmClient {
+        AsyncSwarmClient::new(self)
+    }
+
+    pub fn networks(&self) -> AsyncNetworksClient {
+        AsyncNetworksClient::new(self)
+    }
+
+    pub fn volumes(&self) -> AsyncVolumesClient {
+        AsyncVolumesClient::new(self)
+    }
+
+    pub fn tasks(&self) -> AsyncTasksClient {
+        AsyncTasksClient::new(self)
+    }
+
+    pub fn secrets(&self) -> AsyncSecretsClient {
+        AsyncSecretsClient::new(self)
+    }
+
+    pub fn system(&self) -> AsyncSystemClient {
+        AsyncSystemClient::new(self)
+    }
+}
+
+async_endpoint!(AsyncImagesClient);
+
+impl AsyncImagesClient {
+    pub fn all(&self) -> DockerFuture<Vec<Image>> {
+        self.client.spawn(|client| client.images().all())
+    }
+
+    pub fn stream(&self) -> DockerStream<Image> {
+        self.client.spawn_stream(|client| get_array_stream(client, "images/json"))
+    }
+}
+
+async_endpoint!(AsyncContainersClient);
+
+impl AsyncContainersClient {
+    pub fn all(&self) -> DockerFuture<Vec<Container>> {
+        self.client.spawn(|client| client.containers().all())
+    }
+
+    pub fn list(&self, options: ContainerListOptions) -> DockerFuture<Vec<Container>> {
+        self.client.spawn(move |client| client.containers().list(&options))
+    }
+
+    pub fn stream(&self) -> DockerStream<Container> {
+        self.client.spawn_stream(|client| get_array_stream(client, "containers/json"))
+    }
+
+    pub fn create(&self, name: Option<&str>, config: ContainerCreateConfig) -> DockerFuture<ContainerCreateResponse> {
+        let name = name.map(String::from);
+        self.client.spawn(move |client| client.containers().create(name.as_ref().map(|name| name.as_str()), &config))
+    }
+
+    pub fn inspect(&self, id: &str, size: bool) -> DockerFuture<ContainerInspect> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().inspect(&id, size))
+    }
+
+    pub fn start(&self, id: &str) -> DockerFuture<bool> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().start(&id))
+    }
+
+    pub fn stop(&self, id: &str, timeout: Option<u32>) -> DockerFuture<bool> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().stop(&id, timeout))
+    }
+
+    pub fn restart(&self, id: &str, timeout: Option<u32>) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().restart(&id, timeout))
+    }
+
+    pub fn kill(&self, id: &str, signal: Option<&str>) -> DockerFuture<()> {
+        let id = String::from(id);
+        let signal = signal.map(String::from);
+        self.client.spawn(move |client| client.containers().kill(&id, signal.as_ref().map(|signal| signal.as_str())))
+    }
+
+    pub fn pause(&self, id: &str) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().pause(&id))
+    }
+
+    pub fn unpause(&self, id: &str) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().unpause(&id))
+    }
+
+    pub fn remove(&self, id: &str, force: bool, volumes: bool, link: bool) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.containers().remove(&id, force, volumes, link))
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> DockerFuture<()> {
+        let id = String::from(id);
+        let name = String::from(name);
+        self.client.spawn(move |client| client.containers().rename(&id, &name))
+    }
+
+    pub fn logs(&self, id: &str, options: LogOptions) -> DockerStream<LogOutput> {
+        let id = String::from(id);
+        self.client.spawn_stream(move |client| client.containers().logs(&id, &options))
+    }
+
+    pub fn log_lines(&self, id: &str, options: LogOptions) -> DockerStream<LogLine> {
+        let id = String::from(id);
+        self.client.spawn_stream(move |client| client.containers().log_lines(&id, &options))
+    }
+}
+
+async_endpoint!(AsyncNetworksClient);
+
+impl AsyncNetworksClient {
+    pub fn all(&self) -> DockerFuture<Vec<Network>> {
+        self.client.spawn(|client| client.networks().all())
+    }
+
+    pub fn stream(&self) -> DockerStream<Network> {
+        self.client.spawn_stream(|client| get_array_stream(client, "networks"))
+    }
+}
+
+async_endpoint!(AsyncVolumesClient);
+
+impl AsyncVolumesClient {
+    pub fn get(&self) -> DockerFuture<Volumes> {
+        self.client.spawn(|client| client.volumes().get())
+    }
+}
+
+async_endpoint!(AsyncSwarmClient);
+
+impl AsyncSwarmClient {
+    pub fn get(&self) -> DockerFuture<Swarm> {
+        self.client.spawn(|client| client.swarm().get())
+    }
+}
+
+async_endpoint!(AsyncTasksClient);
+
+impl AsyncTasksClient {
+    pub fn get(&self) -> DockerFuture<Vec<Task>> {
+        self.client.spawn(|client| client.tasks().get())
+    }
+
+    pub fn stream(&self) -> DockerStream<Task> {
+        self.client.spawn_stream(|client| get_array_stream(client, "tasks"))
+    }
+}
+
+async_endpoint!(AsyncSecretsClient);
+
+impl AsyncSecretsClient {
+    pub fn all(&self) -> DockerFuture<Vec<Secret>> {
+        self.client.spawn(|client| client.secrets().all())
+    }
+
+    pub fn stream(&self) -> DockerStream<Secret> {
+        self.client.spawn_stream(|client| {
+            client.require_api_version(SECRETS_API_VERSION)?;
+            get_array_stream(client, "secrets")
+        })
+    }
+
+    pub fn inspect(&self, id: &str) -> DockerFuture<Secret> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.secrets().inspect(&id))
+    }
+
+    pub fn create(&self, spec: SecretSpec) -> DockerFuture<()> {
+        self.client.spawn(move |client| client.secrets().create(&spec))
+    }
+
+    pub fn update(&self, id: &str, spec: SecretSpec) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.secrets().update(&id, &spec))
+    }
+
+    pub fn delete(&self, id: &str) -> DockerFuture<()> {
+        let id = String::from(id);
+        self.client.spawn(move |client| client.secrets().delete(&id))
+    }
+}
+
+async_endpoint!(AsyncSystemClient);
+
+impl AsyncSystemClient {
+    pub fn ping(&self) -> DockerFuture<String> {
+        self.client.spawn(|client| client.system().ping())
+    }
+
+    pub fn version(&self) -> DockerFuture<Version> {
+        self.client.spawn(|client| client.system().version())
+    }
+
+    // Events as the daemon reports them, the stream stays open
+    pub fn events(&self) -> DockerStream<Event> {
+        self.client.spawn_stream(|client| client.system().events())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {
+        use futures::{Future, Stream};
+        use async_client::AsyncClient;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc"}));
+        let client = AsyncClient::new(daemon.client());
+        let images = client.images().all().wait();
+        assert!(images.is_ok());
+        let containers = client.containers().stream().collect().wait();
+        assert!(containers.unwrap().len() == 1);
+    }
+
+    #[test]
+    fn requests_run_on_the_pool() {
+        use futures::Future;
+        use async_client::AsyncClient;
+        use Client;
+        let client = AsyncClient::new(Client::builder().build().unwrap());
+        let version = client.spawn(|client| Ok(String::from(client.api_version()))).wait();
+        assert!(version.unwrap() == "1.27");
+    }
+
+    #[test]
+    fn streams_items_as_they_arrive() {
+        use futures::Stream;
+        use async_client::AsyncClient;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_event(&json!({"Type": "container", "Action": "start", "Actor": {"ID": "abc"}}));
+        let client = AsyncClient::new(daemon.client());
+
+        // the events stream never ends, the first event has to come
+        // through on its own
+        let mut events = client.system().events().wait();
+        let event = events.next().unwrap().unwrap();
+        assert!(event.action == Some(String::from("start")));
+    }
+
+    #[test]
+    fn container_lifecycle_and_logs() {
+        use futures::{Future, Stream};
+        use async_client::AsyncClient;
+        use common::LogOutput;
+        use containers::LogOptions;
+        use containers::schema::ContainerCreateConfig;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1", "RepoTags": ["busybox:latest"]}));
+        let client = AsyncClient::new(daemon.client());
+        let containers = client.containers();
+
+        let id = containers.create(Some("job"), ContainerCreateConfig::new("busybox:latest")).wait().unwrap().id;
+        assert!(containers.start(id.as_str()).wait().unwrap());
+        daemon.add_log(id.as_str(), "stdout", "hello\n");
+        assert!(containers.stop(id.as_str(), None).wait().unwrap());
+        assert!(containers.inspect(id.as_str(), false).wait().unwrap().state.unwrap().status == Some(String::from("exited")));
+
+        let outputs = containers.logs(id.as_str(), LogOptions::new()).collect().wait().unwrap();
+        assert!(outputs == vec![LogOutput::StdOut(Vec::from(&b"hello\n"[..]))]);
+
+        containers.rename(id.as_str(), "done").wait().unwrap();
+        containers.remove(id.as_str(), false, false, false).wait().unwrap();
+        assert!(containers.stream().collect().wait().unwrap().is_empty());
+    }
+}
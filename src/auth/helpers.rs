@@ -0,0 +1,159 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Credential helpers keep registry passwords out of `config.json`,
+// e.g. in the system keychain. `docker-credential-<name>` is run with
+// the action as its only argument and talks json over stdin/stdout.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process::{Command, ExitStatus, Stdio};
+
+use serde_json;
+
+use common::from_json;
+use error::DockerError;
+use super::RegistryAuth;
+
+// Username helpers report for identity tokens
+const TOKEN_USERNAME: &'static str = "<token>";
+
+// What helpers answer when they have nothing for a server
+const NOT_FOUND: &'static str = "credentials not found in native keychain";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Credentials {
+   #[serde(rename = "ServerURL")]
+   #[serde(default)]
+   server_url : String,
+
+   #[serde(rename = "Username")]
+   #[serde(default)]
+   username : String,
+
+   #[serde(rename = "Secret")]
+   #[serde(default)]
+   secret : String
+}
+
+// How a helper that didn't succeed exited and what it printed
+struct Failure {
+    status: ExitStatus,
+    stdout: String,
+    stderr: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialHelper {
+    program: String
+}
+
+impl CredentialHelper {
+    // Runs `docker-credential-<name>` found on PATH
+    pub fn new(name: &str) -> CredentialHelper {
+        CredentialHelper::with_program(format!("docker-credential-{}", name).as_str())
+    }
+
+    pub fn with_program(program: &str) -> CredentialHelper {
+        CredentialHelper { program: String::from(program) }
+    }
+
+    fn run(&self, action: &str, input: &[u8]) -> Result<Result<String, Failure>, DockerError> {
+        let mut child = Command::new(self.program.as_str())
+            .arg(action)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| DockerError::Docker(format!("could not run {}: {}", self.program, error)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // helpers that need no input or fail early exit without
+            // reading it, how they exited tells what went wrong
+            match stdin.write_all(input) {
+                Err(ref error) if error.kind() == io::ErrorKind::BrokenPipe => (),
+                result => result?
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        let stdout = String::from(String::from_utf8_lossy(&output.stdout).trim());
+        if output.status.success() {
+            return Ok(Ok(stdout));
+        }
+        Ok(Err(Failure {
+            status: output.status,
+            stdout: stdout,
+            stderr: String::from(String::from_utf8_lossy(&output.stderr).trim())
+        }))
+    }
+
+    fn failed(&self, action: &str, failure: Failure) -> DockerError {
+        let mut message = format!("{} {} failed with {}", self.program, action, failure.status);
+        for output in [failure.stdout, failure.stderr].iter().filter(|output| !output.is_empty()) {
+            message.push_str(": ");
+            message.push_str(output.as_str());
+        }
+        DockerError::Docker(message)
+    }
+
+    // Credentials stored for `server`, `None` when there are none
+    pub fn get(&self, server: &str) -> Result<Option<RegistryAuth>, DockerError> {
+        let output = match self.run("get", server.as_bytes())? {
+            Ok(output) => output,
+            Err(ref failure) if failure.stdout == NOT_FOUND => return Ok(None),
+            Err(failure) => return Err(self.failed("get", failure))
+        };
+
+        let credentials : Credentials = from_json(output.as_str())?;
+        let auth = if credentials.username == TOKEN_USERNAME {
+            RegistryAuth::token(credentials.secret.as_str())
+        } else {
+            RegistryAuth::new(credentials.username.as_str(), credentials.secret.as_str())
+        };
+        Ok(Some(auth.server_address(server)))
+    }
+
+    // Saves username and password or the identity token of `auth`
+    // under its server address
+    pub fn store(&self, auth: &RegistryAuth) -> Result<(), DockerError> {
+        let credentials = match auth.identity_token {
+            Some(ref token) => Credentials {
+                server_url: auth.server_address.clone().unwrap_or_default(),
+                username: String::from(TOKEN_USERNAME),
+                secret: token.clone()
+            },
+            None => Credentials {
+                server_url: auth.server_address.clone().unwrap_or_default(),
+                username: auth.username.clone().unwrap_or_default(),
+                secret: auth.password.clone().unwrap_or_default()
+            }
+        };
+        let input = serde_json::to_vec(&credentials).map_err(|error| DockerError::Json {
+            error: error,
+            payload: String::new()
+        })?;
+
+        self.run("store", &input)?.map(|_| ()).map_err(|failure| self.failed("store", failure))
+    }
+
+    pub fn erase(&self, server: &str) -> Result<(), DockerError> {
+        self.run("erase", server.as_bytes())?.map(|_| ()).map_err(|failure| self.failed("erase", failure))
+    }
+
+    // Usernames by server of everything the helper has stored
+    pub fn list(&self) -> Result<HashMap<String, String>, DockerError> {
+        let output = self.run("list", &[])?.map_err(|failure| self.failed("list", failure))?;
+        from_json(output.as_str())
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Credentials for private registries. The daemon expects them base64url
+// encoded in the `X-Registry-Auth` header of pull, push and service
+// requests. Those of `docker login` are read from `~/.docker/config.json`
+// or the credential helpers it names.
+
+pub mod helpers;
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use base64;
+use serde_json;
+
+use common::from_json;
+use error::DockerError;
+
+pub use self::helpers::CredentialHelper;
+
+pub const REGISTRY_AUTH_HEADER: &'static str = "X-Registry-Auth";
+
+// Where `docker login` stores credentials for Docker Hub
+pub const DOCKER_HUB: &'static str = "https://index.docker.io/v1/";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct RegistryAuth {
+   #[serde(rename = "username")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub username : Option<String>,
+
+   #[serde(rename = "password")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub password : Option<String>,
+
+   #[serde(rename = "email")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub email : Option<String>,
+
+   #[serde(rename = "serveraddress")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub server_address : Option<String>,
+
+   // Token handed out by the registry, replaces username and password
+   #[serde(rename = "identitytoken")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub identity_token : Option<String>
+}
+
+impl RegistryAuth {
+    pub fn new(username: &str, password: &str) -> RegistryAuth {
+        RegistryAuth {
+            username: Some(String::from(username)),
+            password: Some(String::from(password)),
+            ..RegistryAuth::default()
+        }
+    }
+
+    pub fn token(identity_token: &str) -> RegistryAuth {
+        RegistryAuth {
+            identity_token: Some(String::from(identity_token)),
+            ..RegistryAuth::default()
+        }
+    }
+
+    pub fn server_address(mut self, address: &str) -> RegistryAuth {
+        self.server_address = Some(String::from(address));
+        self
+    }
+
+    // Value of the `X-Registry-Auth` header
+    pub fn encode(&self) -> Result<String, DockerError> {
+        let json = serde_json::to_vec(self).map_err(|error| DockerError::Json {
+            error: error,
+            payload: String::new()
+        })?;
+        Ok(base64::encode_config(&json, base64::URL_SAFE))
+    }
+}
+
+// Stands in for a secret in debug output, which tends to end up in logs
+fn redacted(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| "<redacted>")
+}
+
+impl fmt::Debug for RegistryAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RegistryAuth")
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("email", &self.email)
+            .field("server_address", &self.server_address)
+            .field("identity_token", &redacted(&self.identity_token))
+            .finish()
+    }
+}
+
+// One of the `auths` in the config file
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct AuthEntry {
+   // base64 of `username:password`
+   #[serde(rename = "auth")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub auth : Option<String>,
+
+   #[serde(rename = "username")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub username : Option<String>,
+
+   #[serde(rename = "password")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub password : Option<String>,
+
+   #[serde(rename = "email")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub email : Option<String>,
+
+   #[serde(rename = "identitytoken")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub identity_token : Option<String>
+}
+
+impl AuthEntry {
+    fn to_registry_auth(&self, address: &str) -> Result<RegistryAuth, DockerError> {
+        let mut auth = RegistryAuth {
+            username: self.username.clone(),
+            password: self.password.clone(),
+            email: self.email.clone(),
+            server_address: Some(String::from(address)),
+            identity_token: self.identity_token.clone()
+        };
+
+        if let Some(ref encoded) = self.auth {
+            let decoded = base64::decode(encoded.trim())
+                .map_err(|error| DockerError::Docker(format!("invalid auth for {}: {}", address, error)))?;
+            let decoded = String::from_utf8(decoded)?;
+            let mut parts = decoded.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(username), Some(password)) => {
+                    auth.username = Some(String::from(username));
+                    auth.password = Some(String::from(password));
+                }
+                _ => return Err(DockerError::Docker(format!("invalid auth for {}: no password", address)))
+            }
+        }
+
+        Ok(auth)
+    }
+}
+
+impl fmt::Debug for AuthEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthEntry")
+            .field("auth", &redacted(&self.auth))
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("email", &self.email)
+            .field("identity_token", &redacted(&self.identity_token))
+            .finish()
+    }
+}
+
+// The parts of the docker cli's `config.json` this crate understands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConfigFile {
+   // Credentials by registry address
+   #[serde(rename = "auths")]
+   #[serde(default)]
+   pub auths : HashMap<String, AuthEntry>,
+
+   // Credential helper for every registry, e.g. `secretservice`
+   #[serde(rename = "credsStore")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub creds_store : Option<String>,
+
+   // Credential helpers by registry host, they take precedence over
+   // `creds_store`
+   #[serde(rename = "credHelpers")]
+   #[serde(default)]
+   pub cred_helpers : HashMap<String, String>,
+
+   // Context `docker context use` switched to
+   #[serde(rename = "currentContext")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub current_context : Option<String>
+}
+
+impl ConfigFile {
+    // Reads `config.json` below DOCKER_CONFIG or `~/.docker`
+    pub fn from_env() -> Result<ConfigFile, DockerError> {
+        ConfigFile::from_vars(|name| env::var(name).ok())
+    }
+
+    pub fn from_vars<F>(var: F) -> Result<ConfigFile, DockerError>
+        where F: Fn(&str) -> Option<String>
+    {
+        ConfigFile::load(config_dir(var).join("config.json"))
+    }
+
+    // A missing file is the same as one without any settings
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ConfigFile, DockerError> {
+        let mut text = String::new();
+        match File::open(path) {
+            Ok(mut file) => file.read_to_string(&mut text)?,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(ConfigFile::default()),
+            Err(error) => return Err(DockerError::from(error))
+        };
+        from_json(text.as_str())
+    }
+
+    // Helper that stores the credentials for the registry at `address`
+    pub fn helper(&self, address: &str) -> Option<CredentialHelper> {
+        let host = registry_host(address);
+        self.cred_helpers.iter()
+            .find(|&(key, _)| registry_host(key) == host)
+            .map(|(_, name)| name)
+            .or(self.creds_store.as_ref())
+            .map(|name| CredentialHelper::new(name))
+    }
+
+    // Stored credentials for the registry at `address`, which can be
+    // a bare host or a url. Credential helpers are asked first.
+    pub fn auth(&self, address: &str) -> Result<Option<RegistryAuth>, DockerError> {
+        let host = registry_host(address);
+        if let Some(helper) = self.helper(address) {
+            for server in self.helper_servers(address).iter() {
+                if let Some(auth) = helper.get(server)? {
+                    return Ok(Some(auth));
+                }
+            }
+        }
+
+        for (key, entry) in self.auths.iter() {
+            if registry_host(key) == host {
+                return entry.to_registry_auth(key).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    // Names helpers may know the registry at `address` by. Helpers
+    // store credentials under the exact server `docker login` was
+    // given, so that and the keys of the config file come before the
+    // bare host.
+    fn helper_servers(&self, address: &str) -> Vec<String> {
+        let host = registry_host(address);
+        let mut servers = vec![String::from(address)];
+        let keys = self.cred_helpers.keys().chain(self.auths.keys());
+        servers.extend(keys.filter(|key| registry_host(key) == host).cloned());
+        if host == registry_host(DOCKER_HUB) {
+            servers.push(String::from(DOCKER_HUB));
+        }
+        servers.push(host);
+
+        let mut unique : Vec<String> = Vec::new();
+        for server in servers.into_iter() {
+            if !unique.contains(&server) {
+                unique.push(server);
+            }
+        }
+        unique
+    }
+
+    // Credentials for the registry `image` is pulled from
+    pub fn image_auth(&self, image: &str) -> Result<Option<RegistryAuth>, DockerError> {
+        self.auth(image_registry(image).as_str())
+    }
+}
+
+// Directory of the docker cli's settings, DOCKER_CONFIG or `~/.docker`
+pub fn config_dir<F>(var: F) -> PathBuf
+    where F: Fn(&str) -> Option<String>
+{
+    let var = |name: &str| var(name).and_then(|value| {
+        if value.is_empty() { None } else { Some(value) }
+    });
+
+    match var("DOCKER_CONFIG") {
+        Some(path) => PathBuf::from(path),
+        None => var("HOME").map(|home| Path::new(&home).join(".docker"))
+                           .unwrap_or(PathBuf::from(".docker"))
+    }
+}
+
+// Host part of a registry address, all the names of Docker Hub become
+// `index.docker.io`
+pub fn registry_host(address: &str) -> String {
+    let address = match address.find("://") {
+        Some(index) => &address[index + 3..],
+        None => address
+    };
+    let host = address.split('/').next().unwrap_or("");
+    match host {
+        "docker.io" | "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" =>
+            String::from("index.docker.io"),
+        _ => String::from(host)
+    }
+}
+
+// Registry an image is pulled from, e.g. `quay.io` for
+// `quay.io/coreos/etcd` and Docker Hub for `alpine:latest`
+pub fn image_registry(image: &str) -> String {
+    match image.find('/') {
+        Some(index) => {
+            let first = &image[..index];
+            if first.contains('.') || first.contains(':') || first == "localhost" {
+                String::from(first)
+            } else {
+                String::from(DOCKER_HUB)
+            }
+        }
+        None => String::from(DOCKER_HUB)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encodes_header() {
+        use base64;
+        use serde_json::{self, Value};
+        use auth::RegistryAuth;
+        let auth = RegistryAuth::new("joe", "s3cr?t>").server_address("registry.example.com");
+        let encoded = auth.encode().unwrap();
+        assert!(!encoded.contains('+') && !encoded.contains('/'));
+
+        let json : Value = serde_json::from_slice(&base64::decode_config(&encoded, base64::URL_SAFE).unwrap()).unwrap();
+        assert!(json == json!({"username": "joe", "password": "s3cr?t>", "serveraddress": "registry.example.com"}));
+
+        let json : Value = serde_json::from_slice(&base64::decode_config(&RegistryAuth::token("abc").encode().unwrap(),
+                                                                         base64::URL_SAFE).unwrap()).unwrap();
+        assert!(json == json!({"identitytoken": "abc"}));
+    }
+
+    #[test]
+    fn redacts_debug_output() {
+        use serde_json;
+        use auth::{ConfigFile, RegistryAuth};
+        let output = format!("{:?}", RegistryAuth::new("joe", "s3cret").server_address("quay.io"));
+        assert!(output.contains("joe") && output.contains("quay.io"));
+        assert!(!output.contains("s3cret"));
+        assert!(!format!("{:?}", RegistryAuth::token("t0ken")).contains("t0ken"));
+
+        let config : ConfigFile = serde_json::from_str(r#"{
+            "auths": {"quay.io": {"auth": "am9lOnMzY3JldA==", "password": "s3cret"}}
+        }"#).unwrap();
+        let output = format!("{:?}", config);
+        assert!(output.contains("<redacted>"));
+        assert!(!output.contains("s3cret") && !output.contains("am9lOnMzY3JldA=="));
+    }
+
+    #[test]
+    fn registry_hosts() {
+        use auth::{image_registry, registry_host, DOCKER_HUB};
+        assert!(registry_host(DOCKER_HUB) == "index.docker.io");
+        assert!(registry_host("docker.io") == "index.docker.io");
+        assert!(registry_host("https://registry.example.com:5000/v2/") == "registry.example.com:5000");
+        assert!(image_registry("alpine:latest") == DOCKER_HUB);
+        assert!(image_registry("library/alpine") == DOCKER_HUB);
+        assert!(image_registry("quay.io/coreos/etcd") == "quay.io");
+        assert!(image_registry("localhost:5000/app") == "localhost:5000");
+    }
+
+    #[test]
+    fn reads_config_file() {
+        use std::env;
+        use std::fs::{self, File};
+        use std::io::Write;
+        use auth::{ConfigFile, RegistryAuth, DOCKER_HUB};
+        let dir = env::temp_dir().join(format!("docker-rust-auth-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        File::create(dir.join("config.json")).unwrap().write_all(br#"{
+            "auths": {
+                "https://index.docker.io/v1/": {"auth": "am9lOnNlY3JldDpwYXJ0"},
+                "registry.example.com": {"identitytoken": "token"}
+            },
+            "HttpHeaders": {"User-Agent": "Docker-Client"}
+        }"#).unwrap();
+
+        let dir_name = dir.to_str().unwrap().to_owned();
+        let config = ConfigFile::from_vars(|name| if name == "DOCKER_CONFIG" { Some(dir_name.clone()) } else { None });
+        let _ = fs::remove_dir_all(&dir);
+        let config = config.unwrap();
+
+        let hub = config.auth("docker.io").unwrap().unwrap();
+        assert!(hub == RegistryAuth::new("joe", "secret:part").server_address(DOCKER_HUB));
+        let private = config.auth("https://registry.example.com/v2/").unwrap().unwrap();
+        assert!(private.identity_token == Some(String::from("token")));
+        assert!(config.auth("quay.io").unwrap().is_none());
+    }
+
+    #[test]
+    fn picks_credential_helper() {
+        use serde_json;
+        use auth::{ConfigFile, CredentialHelper};
+        let config : ConfigFile = serde_json::from_str(r#"{
+            "auths": {"quay.io": {}},
+            "credsStore": "secretservice",
+            "credHelpers": {"123.dkr.ecr.us-east-1.amazonaws.com": "ecr-login"}
+        }"#).unwrap();
+
+        assert!(config.helper("https://123.dkr.ecr.us-east-1.amazonaws.com/v2/") ==
+                Some(CredentialHelper::new("ecr-login")));
+        assert!(config.helper("quay.io") == Some(CredentialHelper::new("secretservice")));
+        assert!(ConfigFile::default().helper("quay.io").is_none());
+    }
+
+    #[test]
+    fn helper_server_names() {
+        use serde_json;
+        use auth::{ConfigFile, DOCKER_HUB};
+        let config : ConfigFile = serde_json::from_str(r#"{
+            "auths": {"https://registry.example.com": {}, "quay.io": {}},
+            "credsStore": "secretservice"
+        }"#).unwrap();
+
+        assert!(config.helper_servers("registry.example.com") ==
+                vec!["registry.example.com", "https://registry.example.com"]);
+        assert!(config.helper_servers("https://registry.example.com/v2/") ==
+                vec!["https://registry.example.com/v2/", "https://registry.example.com", "registry.example.com"]);
+        assert!(config.helper_servers("docker.io") == vec!["docker.io", DOCKER_HUB, "index.docker.io"]);
+        assert!(config.helper_servers(DOCKER_HUB) == vec![DOCKER_HUB, "index.docker.io"]);
+    }
+
+    #[test]
+    fn missing_config_file() {
+        use auth::ConfigFile;
+        let config = ConfigFile::from_vars(|name| if name == "DOCKER_CONFIG" {
+            Some(String::from("/nonexistent/docker-rust"))
+        } else {
+            None
+        });
+        assert!(config.unwrap().auths.is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::Client;
+use config::{parse_host, Config, Host, TlsConfig};
+use error::DockerError;
+use transport::{self, RecordingTransport, ReplayTransport, Transport};
+
+// Configures a `Client` piece by piece instead of through the
+// environment
+//
+//     let client = ClientBuilder::new()
+//         .socket("/run/user/1000/docker.sock")
+//         .api_version("1.25")
+//         .connect_timeout(Duration::from_secs(5))
+//         .build()?;
+pub struct ClientBuilder {
+    config: Result<Config, DockerError>,
+    transport: Option<Arc<Transport>>,
+    replay: Option<PathBuf>,
+    record: Option<PathBuf>,
+    negotiate: bool
+}
+
+impl ClientBuilder {
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            config: Ok(Config::default()),
+            transport: None,
+            replay: None,
+            record: None,
+            negotiate: false
+        }
+    }
+
+    // Starts from what `Client::from_env` would use
+    pub fn from_env() -> ClientBuilder {
+        ClientBuilder {
+            config: Config::from_env(),
+            transport: None,
+            replay: None,
+            record: None,
+            negotiate: false
+        }
+    }
+
+    // Starts from the settings of a docker cli context
+    pub fn from_context(name: &str) -> ClientBuilder {
+        ClientBuilder {
+            config: Config::from_context(name),
+            transport: None,
+            replay: None,
+            record: None,
+            negotiate: false
+        }
+    }
+
+    fn update<F>(mut self, f: F) -> ClientBuilder
+        where F: FnOnce(&mut Config) -> Result<(), DockerError>
+    {
+        self.config = self.config.and_then(|mut config| {
+            f(&mut config)?;
+            Ok(config)
+        });
+        self
+    }
+
+    pub fn socket(self, path: &str) -> ClientBuilder {
+        self.update(|config| {
+            config.host = Host::Unix(String::from(path));
+            config.tls = None;
+            Ok(())
+        })
+    }
+
+    // Accepts the same values as DOCKER_HOST
+    pub fn host(self, host: &str) -> ClientBuilder {
+        self.update(|config| {
+            let (host, https) = parse_host(host, config.tls.is_some())?;
+            config.host = host;
+            if https && config.tls.is_none() {
+                config.tls = Some(TlsConfig {
+                    ca: None,
+                    cert: None,
+                    key: None,
+                    verify: true,
+                    verify_hostname: true,
+                    server_name: None
+                });
+            } else if !https {
+                config.tls = None;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn tls(self, tls: TlsConfig) -> ClientBuilder {
+        self.update(|config| {
+            config.tls = Some(tls);
+            Ok(())
+        })
+    }
+
+    pub fn api_version(self, version: &str) -> ClientBuilder {
+        self.update(|config| {
+            let version = if version.starts_with('v') { &version[1..] } else { version };
+            config.api_version = String::from(version);
+            Ok(())
+        })
+    }
+
+    pub fn connect_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.update(|config| {
+            config.connect_timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    pub fn read_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.update(|config| {
+            config.read_timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    pub fn user_agent(self, user_agent: &str) -> ClientBuilder {
+        self.update(|config| {
+            config.user_agent = String::from(user_agent);
+            Ok(())
+        })
+    }
+
+    pub fn header(self, name: &str, value: &str) -> ClientBuilder {
+        self.update(|config| {
+            config.headers.push((String::from(name), String::from(value)));
+            Ok(())
+        })
+    }
+
+    // Program used to reach ssh:// hosts instead of `ssh`
+    pub fn ssh_command(self, command: &str) -> ClientBuilder {
+        self.update(|config| {
+            config.ssh_command = String::from(command);
+            Ok(())
+        })
+    }
+
+    // Use `transport` instead of curl, host and TLS settings are then
+    // up to the transport
+    pub fn transport(mut self, transport: Arc<Transport>) -> ClientBuilder {
+        self.transport = Some(transport);
+        self
+    }
+
+    // Write every exchange with the daemon to a fixture file
+    pub fn record<P: AsRef<Path>>(mut self, path: P) -> ClientBuilder {
+        self.record = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    // Answer requests from a fixture file instead of a daemon
+    pub fn replay<P: AsRef<Path>>(mut self, path: P) -> ClientBuilder {
+        self.replay = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    // Ask the daemon for its API version when building the client
+    pub fn negotiate_version(mut self) -> ClientBuilder {
+        self.negotiate = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Client, DockerError> {
+        let config = self.config?;
+        let mut transport : Arc<Transport> = match (self.replay, self.transport) {
+            (Some(path), _) => Arc::new(ReplayTransport::from_file(&path)?),
+            (None, Some(transport)) => transport,
+            (None, None) => transport::for_config(&config)
+        };
+        if let Some(path) = self.record {
+            transport = Arc::new(RecordingTransport::new(transport, &path));
+        }
+
+        let mut client = Client::with_transport(config, transport);
+        if self.negotiate {
+            client.negotiate_version()?;
+        }
+
+        Ok(client)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use builder::ClientBuilder;
+    use config::Host;
+
+    #[test]
+    fn socket_and_version() {
+        let client = ClientBuilder::new()
+            .socket("/run/user/1000/docker.sock")
+            .api_version("v1.25")
+            .connect_timeout(Duration::from_secs(5))
+            .user_agent("my-tool/1.0")
+            .header("X-Meta", "yes")
+            .build()
+            .unwrap();
+
+        let config = client.config();
+        assert!(config.host == Host::Unix(String::from("/run/user/1000/docker.sock")));
+        assert!(config.base_url() == "http://localhost/v1.25/");
+        assert!(config.connect_timeout == Some(Duration::from_secs(5)));
+        assert!(config.user_agent == "my-tool/1.0");
+        assert!(config.headers == vec![(String::from("X-Meta"), String::from("yes"))]);
+    }
+
+    #[test]
+    fn tcp_host() {
+        let client = ClientBuilder::new().host("tcp://127.0.0.1:2375").build().unwrap();
+        assert!(client.config().base_url() == "http://127.0.0.1:2375/v1.27/");
+
+        let client = ClientBuilder::new().host("https://docker.local").build().unwrap();
+        assert!(client.config().base_url() == "https://docker.local:2376/v1.27/");
+    }
+
+    #[test]
+    fn invalid_host_fails_on_build() {
+        assert!(ClientBuilder::new().host("fd://").api_version("1.25").build().is_err());
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use error::DockerError;
+use super::from_json;
+
+// Decodes the concatenated json objects that pull, push, build, events
+// and stats answer with. Objects may be split across reads in any
+// place and don't have to be separated by newlines. An object with an
+// `error` key is turned into a `DockerError`.
+pub struct JsonStream<T> {
+    reader: Box<Read + Send>,
+    buffer: Vec<u8>,
+
+    // decodes the elements of a single json array instead
+    array: bool,
+    opened: bool,
+    closed: bool,
+
+    // where scanning for the end of the current value stopped and
+    // where in the buffer the value starts
+    scanned: usize,
+    start: usize,
+    depth: usize,
+    started: bool,
+    scalar: bool,
+    in_string: bool,
+    escaped: bool,
+    finished: bool,
+    marker: PhantomData<T>
+}
+
+impl<T: Deserialize> JsonStream<T> {
+    pub fn new<R: Read + Send + 'static>(reader: R) -> JsonStream<T> {
+        JsonStream {
+            reader: Box::new(reader),
+            buffer: Vec::new(),
+            array: false,
+            opened: false,
+            closed: false,
+            scanned: 0,
+            start: 0,
+            depth: 0,
+            started: false,
+            scalar: false,
+            in_string: false,
+            escaped: false,
+            finished: false,
+            marker: PhantomData
+        }
+    }
+
+    // Decodes the elements of the json array list endpoints answer
+    // with one by one, without waiting for the whole array
+    pub fn array<R: Read + Send + 'static>(reader: R) -> JsonStream<T> {
+        let mut stream = JsonStream::new(reader);
+        stream.array = true;
+        stream
+    }
+
+    // Length of the first complete value in the buffer, if there is one
+    fn scan(&mut self) -> Option<usize> {
+        while self.scanned < self.buffer.len() {
+            let byte = self.buffer[self.scanned];
+            self.scanned += 1;
+
+            if !self.started {
+                match byte {
+                    b' ' | b'\t' | b'\r' | b'\n' => (),
+                    b'[' if self.array && !self.opened => self.opened = true,
+                    b',' if self.array && self.opened => (),
+                    b']' if self.array && self.opened => {
+                        self.closed = true;
+                        return None;
+                    }
+                    b'{' | b'[' => {
+                        self.started = true;
+                        self.depth = 1;
+                        self.start = self.scanned - 1;
+                    }
+                    _ => {
+                        self.started = true;
+                        self.scalar = true;
+                        self.in_string = byte == b'"';
+                        self.start = self.scanned - 1;
+                    }
+                }
+            } else if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+            } else {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' if self.depth > 0 => {
+                        self.depth -= 1;
+                        if self.depth == 0 && !self.scalar {
+                            return Some(self.scanned);
+                        }
+                    }
+                    b' ' | b'\t' | b'\r' | b'\n' if self.scalar => return Some(self.scanned),
+                    b',' | b']' if self.scalar && self.array => {
+                        // the separator is left for the next value
+                        self.scanned -= 1;
+                        return Some(self.scanned);
+                    }
+                    _ => ()
+                }
+            }
+        }
+
+        None
+    }
+
+    fn take(&mut self, length: usize) -> Result<T, DockerError> {
+        let value : Vec<u8> = self.buffer.drain(..length).skip(self.start).collect();
+        self.scanned = 0;
+        self.start = 0;
+        self.depth = 0;
+        self.started = false;
+        self.scalar = false;
+        self.in_string = false;
+        self.escaped = false;
+
+        decode(String::from_utf8(value)?.trim())
+    }
+}
+
+fn decode<T: Deserialize>(text: &str) -> Result<T, DockerError> {
+    let value : Value = from_json(text)?;
+    if let Some(error) = value.get("error") {
+        let message = value.get("errorDetail")
+            .and_then(|detail| detail.get("message"))
+            .unwrap_or(error);
+        return Err(DockerError::Docker(match message.as_str() {
+            Some(message) => String::from(message),
+            None => message.to_string()
+        }));
+    }
+
+    from_json(text)
+}
+
+impl<T: Deserialize> Iterator for JsonStream<T> {
+    type Item = Result<T, DockerError>;
+
+    fn next(&mut self) -> Option<Result<T, DockerError>> {
+        let mut chunk = [0u8; 8192];
+        loop {
+            if self.closed {
+                return None;
+            }
+            if let Some(length) = self.scan() {
+                return Some(self.take(length));
+            }
+            if self.closed {
+                return None;
+            }
+
+            if self.finished {
+                if self.array && self.opened {
+                    // the array was never closed, a started element
+                    // fails to decode with the details
+                    self.closed = true;
+                    if self.started {
+                        let length = self.buffer.len();
+                        return Some(self.take(length));
+                    }
+                    return Some(Err(DockerError::Docker(String::from("stream ended inside a json array"))));
+                }
+                // whatever is left is either a value without a trailing
+                // separator or a truncated object
+                if self.buffer.iter().all(|byte| (*byte as char).is_whitespace()) {
+                    return None;
+                }
+                let length = self.buffer.len();
+                return Some(self.take(length));
+            }
+
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.finished = true,
+                Ok(count) => self.buffer.extend_from_slice(&chunk[..count]),
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => {
+                    self.finished = true;
+                    self.buffer.clear();
+                    return Some(Err(DockerError::from(error)));
+                }
+            }
+        }
+    }
+}
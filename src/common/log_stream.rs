@@ -0,0 +1,274 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::mem;
+
+use error::DockerError;
+
+// Size of the header in front of every frame of multiplexed output
+const HEADER_LENGTH: usize = 8;
+
+// Bytes read at once, larger frames are handed out in several pieces
+const CHUNK_LENGTH: usize = 8192;
+
+// Stream type of frames the daemon reports errors in, e.g. when the
+// log driver fails while the output is sent
+const SYSTEM_ERROR: u8 = 3;
+
+// A piece of container output and where it came from. Output of
+// containers with a tty isn't split up and is all `Console`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogOutput {
+    StdIn(Vec<u8>),
+    StdOut(Vec<u8>),
+    StdErr(Vec<u8>),
+    Console(Vec<u8>)
+}
+
+impl LogOutput {
+    pub fn as_bytes(&self) -> &[u8] {
+        match *self {
+            LogOutput::StdIn(ref data) | LogOutput::StdOut(ref data) |
+            LogOutput::StdErr(ref data) | LogOutput::Console(ref data) => data.as_slice()
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            LogOutput::StdIn(data) | LogOutput::StdOut(data) |
+            LogOutput::StdErr(data) | LogOutput::Console(data) => data
+        }
+    }
+}
+
+// Splits the output of logs, attach and exec into `LogOutput`s. Without
+// a tty every frame starts with a header: the stream type, three zero
+// bytes and the big-endian length of the frame.
+pub struct LogStream {
+    reader: Box<Read + Send>,
+    tty: bool,
+    finished: bool,
+
+    // stream type of the current frame and how much of it is unread
+    kind: u8,
+    remaining: usize
+}
+
+impl LogStream {
+    pub fn new<R: Read + Send + 'static>(reader: R, tty: bool) -> LogStream {
+        LogStream {
+            reader: Box::new(reader),
+            tty: tty,
+            finished: false,
+            kind: 0,
+            remaining: 0
+        }
+    }
+
+    // Like `read_exact` but tells how much it got before the end
+    fn fill(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(count) => filled += count,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error)
+            }
+        }
+        Ok(filled)
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<LogOutput>, DockerError> {
+        if self.tty {
+            let mut chunk = vec![0u8; CHUNK_LENGTH];
+            let count = loop {
+                match self.reader.read(&mut chunk) {
+                    Ok(count) => break count,
+                    Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+                    Err(error) => return Err(DockerError::from(error))
+                }
+            };
+            if count == 0 {
+                return Ok(None);
+            }
+            chunk.truncate(count);
+            return Ok(Some(LogOutput::Console(chunk)));
+        }
+
+        let mut length = self.remaining;
+        if length == 0 {
+            let mut header = [0u8; HEADER_LENGTH];
+            match self.fill(&mut header)? {
+                0 => return Ok(None),
+                HEADER_LENGTH => (),
+                _ => return Err(DockerError::Docker(String::from("stream ended inside a frame header")))
+            }
+
+            // output that isn't multiplexed after all rarely looks like
+            // a header, the length can't be trusted otherwise
+            if header[0] > SYSTEM_ERROR || header[1..4] != [0, 0, 0] {
+                return Err(DockerError::Docker(format!("unknown stream type {:?} in multiplexed output",
+                                                       &header[..4])));
+            }
+            self.kind = header[0];
+            length = (header[4] as usize) << 24 | (header[5] as usize) << 16 |
+                     (header[6] as usize) << 8 | header[7] as usize;
+        }
+
+        // the length comes off the wire, only what is there gets
+        // allocated
+        let mut data = vec![0u8; if length < CHUNK_LENGTH { length } else { CHUNK_LENGTH }];
+        if self.fill(&mut data)? < data.len() {
+            return Err(DockerError::Docker(String::from("stream ended inside a frame")));
+        }
+        self.remaining = length - data.len();
+
+        match self.kind {
+            0 => Ok(Some(LogOutput::StdIn(data))),
+            1 => Ok(Some(LogOutput::StdOut(data))),
+            2 => Ok(Some(LogOutput::StdErr(data))),
+            // the stream ends with the error, the rest of a longer
+            // message is left unread
+            _ => Err(DockerError::Docker(String::from(String::from_utf8_lossy(&data).trim())))
+        }
+    }
+}
+
+impl Iterator for LogStream {
+    type Item = Result<LogOutput, DockerError>;
+
+    fn next(&mut self) -> Option<Result<LogOutput, DockerError>> {
+        if self.finished {
+            return None;
+        }
+
+        match self.next_chunk() {
+            Ok(Some(output)) => Some(Ok(output)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                // frames can't be found again after a broken one
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+// A line of container output. The timestamp is the RFC 3339 one with
+// nanoseconds the daemon puts in front when asked for timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub timestamp: Option<String>,
+    pub output: LogOutput
+}
+
+// Joins the chunks of a `LogStream` into lines, each stream on its own.
+// The last line comes without a newline when the output didn't end
+// with one.
+pub struct LogLines {
+    stream: LogStream,
+    timestamps: bool,
+    // unfinished lines, indexed like the stream types of the headers
+    pending: [Vec<u8>; 4],
+    ready: VecDeque<LogLine>,
+    finished: bool
+}
+
+impl LogLines {
+    // `timestamps` splits the timestamp off every line
+    pub fn new(stream: LogStream, timestamps: bool) -> LogLines {
+        LogLines {
+            stream: stream,
+            timestamps: timestamps,
+            pending: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+            ready: VecDeque::new(),
+            finished: false
+        }
+    }
+
+    fn push(&mut self, output: LogOutput) {
+        let kind = match output {
+            LogOutput::StdIn(_) => 0,
+            LogOutput::StdOut(_) => 1,
+            LogOutput::StdErr(_) => 2,
+            LogOutput::Console(_) => 3
+        };
+        self.pending[kind].extend(output.into_bytes());
+        while let Some(end) = self.pending[kind].iter().position(|&byte| byte == b'\n') {
+            let rest = self.pending[kind].split_off(end + 1);
+            let line = mem::replace(&mut self.pending[kind], rest);
+            let line = self.line(kind, line);
+            self.ready.push_back(line);
+        }
+    }
+
+    fn line(&self, kind: usize, mut data: Vec<u8>) -> LogLine {
+        let mut timestamp = None;
+        if self.timestamps {
+            if let Some(end) = data.iter().position(|&byte| byte == b' ') {
+                timestamp = Some(String::from_utf8_lossy(&data[..end]).into_owned());
+                data = data.split_off(end + 1);
+            }
+        }
+
+        LogLine {
+            timestamp: timestamp,
+            output: match kind {
+                0 => LogOutput::StdIn(data),
+                1 => LogOutput::StdOut(data),
+                2 => LogOutput::StdErr(data),
+                _ => LogOutput::Console(data)
+            }
+        }
+    }
+}
+
+impl Iterator for LogLines {
+    type Item = Result<LogLine, DockerError>;
+
+    fn next(&mut self) -> Option<Result<LogLine, DockerError>> {
+        loop {
+            if let Some(line) = self.ready.pop_front() {
+                return Some(Ok(line));
+            }
+            if self.finished {
+                return None;
+            }
+
+            match self.stream.next() {
+                Some(Ok(output)) => self.push(output),
+                Some(Err(error)) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+                None => {
+                    self.finished = true;
+                    for kind in 0..self.pending.len() {
+                        if !self.pending[kind].is_empty() {
+                            let data = mem::replace(&mut self.pending[kind], Vec::new());
+                            let line = self.line(kind, data);
+                            self.ready.push_back(line);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
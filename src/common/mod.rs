@@ -12,11 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod json_stream;
+mod log_stream;
+
 use super::Client;
 use super::error::DockerError;
+use super::transport::{Request, StreamingResponse};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+pub use self::json_stream::JsonStream;
+pub use self::log_stream::{LogLine, LogLines, LogOutput, LogStream};
+
 macro_rules! endpoint {
     ($sty:ident) => (
         pub struct $sty<'a> {
@@ -33,30 +40,272 @@ macro_rules! endpoint {
     )
 }
 
-pub fn get_vector<T : Deserialize> (client: &Client, path : &str) -> Result<Vec<T>, DockerError> {
-    let result_raw = client.get(path).unwrap();
-    let results : Vec<T> = serde_json::from_str(result_raw.as_str()).unwrap();
+pub fn from_json<T : Deserialize> (payload : &str) -> Result<T, DockerError> {
+    serde_json::from_str(payload).map_err(|error| DockerError::Json {
+        error: error,
+        payload: String::from(payload)
+    })
+}
 
-    Ok(results)    
+pub fn get_vector<T : Deserialize> (client: &Client, path : &str) -> Result<Vec<T>, DockerError> {
+    get(client, path)
 }
 
 pub fn get<T : Deserialize> (client: &Client, path : &str) -> Result<T, DockerError> {
-    let result_raw = client.get(path).unwrap();
-    let result : T = serde_json::from_str(result_raw.as_str()).unwrap();
+    let result_raw = get_text(client, path)?;
+    from_json(result_raw.as_str())
+}
 
-    Ok(result)    
+pub fn get_text(client: &Client, path : &str) -> Result<String, DockerError> {
+    client.execute(Request::new("GET", path))?.text()
+}
+
+pub fn get_stream(client: &Client, path : &str) -> Result<StreamingResponse, DockerError> {
+    client.execute_stream(Request::new("GET", path))
+}
+
+// Decodes the elements of a list response while it arrives, for the
+// streams of the async client
+#[cfg(feature = "async")]
+pub fn get_array_stream<T : Deserialize> (client: &Client, path : &str) -> Result<JsonStream<T>, DockerError> {
+    Ok(JsonStream::array(get_stream(client, path)?))
 }
 
 pub fn post<T : Serialize> (client: &Client, path : &str, payload : &T) -> Result<(), DockerError> {
-    let payload_raw = serde_json::to_string(payload).unwrap();
-    let result_raw = client.post(path, payload_raw.as_bytes());
+    client.execute(Request::new("POST", path).with_json(payload)?)?;
 
     Ok(())
 }
 
 pub fn delete(client: &Client, path : &str) -> Result<(), DockerError> {
-    let result_raw = client.delete(path).unwrap();
-//    let result : T = serde_json::from_str(result_raw.as_str()).unwrap();
+    client.execute(Request::new("DELETE", path))?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    // Hands out at most one byte per read, so every object gets split
+    struct Trickle(Vec<u8>, usize);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.1 >= self.0.len() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[self.1];
+            self.1 += 1;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn json_stream_across_reads() {
+        use serde_json::Value;
+        use common::JsonStream;
+        let payload = "{\"status\":\"Pulling {x}\"}{\"id\":\"a\\\"b\"}\n\n[1,2] {\"progressDetail\":{}}";
+        let values : Vec<Value> = JsonStream::new(Trickle(Vec::from(payload.as_bytes()), 0))
+            .map(|value| value.unwrap())
+            .collect();
+        assert!(values.len() == 4);
+        assert!(values[0]["status"] == "Pulling {x}");
+        assert!(values[1]["id"] == "a\"b");
+        assert!(values[2][1] == 2);
+    }
+
+    #[test]
+    fn json_stream_typed() {
+        use std::io::Cursor;
+        use common::JsonStream;
+        use system::schema::Event;
+        let payload = "{\"Type\":\"container\",\"Action\":\"start\"}\n{\"Type\":\"image\",\"Action\":\"pull\"}\n";
+        let events : Vec<Event> = JsonStream::new(Cursor::new(payload))
+            .map(|event| event.unwrap())
+            .collect();
+        assert!(events[1].event_type == Some(String::from("image")));
+    }
+
+    #[test]
+    fn json_stream_array() {
+        use std::io::Cursor;
+        use serde_json::Value;
+        use common::JsonStream;
+        use error::DockerError;
+        let payload = " [ {\"Id\":\"a]\",\"Ports\":[1,2]} ,{\"Id\":\"b\"}, 3,\"c\"]\n";
+        let values : Vec<Value> = JsonStream::array(Trickle(Vec::from(payload.as_bytes()), 0))
+            .map(|value| value.unwrap())
+            .collect();
+        assert!(values == vec![json!({"Id": "a]", "Ports": [1, 2]}), json!({"Id": "b"}), json!(3), json!("c")]);
+
+        let empty : Vec<_> = JsonStream::<Value>::array(Cursor::new("[]")).collect();
+        assert!(empty.is_empty());
+
+        let mut truncated : JsonStream<Value> = JsonStream::array(Cursor::new("[{\"Id\":\"a\"},{\"Id\""));
+        assert!(truncated.next().unwrap().is_ok());
+        match truncated.next() {
+            Some(Err(DockerError::Json { .. })) => (),
+            other => panic!("expected a truncated element, got {:?}", other)
+        }
+        assert!(truncated.next().is_none());
+    }
+
+    #[test]
+    fn json_stream_errors() {
+        use std::io::Cursor;
+        use serde_json::Value;
+        use common::JsonStream;
+        use error::DockerError;
+        let payload = "{\"status\":\"Pulling\"}\n{\"errorDetail\":{\"message\":\"not found\"},\"error\":\"not found\"}\n{\"status\":";
+        let mut stream : JsonStream<Value> = JsonStream::new(Cursor::new(payload));
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(DockerError::Docker(ref message))) if message == "not found" => (),
+            other => panic!("expected the embedded error, got {:?}", other)
+        }
+        match stream.next() {
+            Some(Err(DockerError::Json { .. })) => (),
+            other => panic!("expected a truncated object, got {:?}", other)
+        }
+        assert!(stream.next().is_none());
+    }
+
+    fn frame(stream: u8, data: &[u8]) -> Vec<u8> {
+        let length = data.len();
+        let mut frame = vec![stream, 0, 0, 0, (length >> 24) as u8, (length >> 16) as u8,
+                             (length >> 8) as u8, length as u8];
+        frame.extend_from_slice(data);
+        frame
+    }
+
+    #[test]
+    fn log_stream_across_reads() {
+        use common::{LogOutput, LogStream};
+        let mut payload = frame(1, b"hello\n");
+        payload.extend(frame(2, b"oops\n"));
+        payload.extend(frame(1, &[b'x'; 300]));
+        payload.extend(frame(0, b""));
+
+        let outputs : Vec<LogOutput> = LogStream::new(Trickle(payload, 0), false)
+            .map(|output| output.unwrap())
+            .collect();
+        assert!(outputs.len() == 4);
+        assert!(outputs[0] == LogOutput::StdOut(Vec::from(&b"hello\n"[..])));
+        assert!(outputs[1] == LogOutput::StdErr(Vec::from(&b"oops\n"[..])));
+        assert!(outputs[2].as_bytes().len() == 300);
+        assert!(outputs[3] == LogOutput::StdIn(Vec::new()));
+    }
+
+    #[test]
+    fn log_stream_large_frames() {
+        use std::io::Cursor;
+        use common::{LogOutput, LogStream};
+        use error::DockerError;
+        let payload = frame(2, &vec![b'x'; 20000]);
+        let outputs : Vec<LogOutput> = LogStream::new(Cursor::new(payload), false)
+            .map(|output| output.unwrap())
+            .collect();
+        assert!(outputs.iter().map(|output| output.as_bytes().len()).collect::<Vec<_>>() == vec![8192, 8192, 3616]);
+        assert!(outputs.iter().all(|output| match *output {
+            LogOutput::StdErr(_) => true,
+            _ => false
+        }));
+
+        // a length of 4 GiB only gets as far as the data that is there
+        let mut huge = vec![1u8, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        huge.extend_from_slice(b"short");
+        let mut outputs = LogStream::new(Cursor::new(huge), false);
+        match outputs.next() {
+            Some(Err(DockerError::Docker(_))) => (),
+            other => panic!("expected a truncated frame, got {:?}", other)
+        }
+
+        // tty output read as multiplexed
+        let mut outputs = LogStream::new(Cursor::new(&b"\x01hello world"[..]), false);
+        match outputs.next() {
+            Some(Err(DockerError::Docker(ref message))) if message.starts_with("unknown stream type") => (),
+            other => panic!("expected an invalid header, got {:?}", other)
+        }
+        assert!(outputs.next().is_none());
+    }
+
+    #[test]
+    fn log_stream_system_errors() {
+        use std::io::Cursor;
+        use common::{LogOutput, LogStream};
+        use error::DockerError;
+        let mut payload = frame(1, b"hello\n");
+        payload.extend(frame(3, b"error from daemon in stream: log driver failed\n"));
+        payload.extend(frame(1, b"never read\n"));
+
+        let mut outputs = LogStream::new(Cursor::new(payload), false);
+        assert!(outputs.next().unwrap().unwrap() == LogOutput::StdOut(Vec::from(&b"hello\n"[..])));
+        match outputs.next() {
+            Some(Err(DockerError::Docker(ref message))) if message == "error from daemon in stream: log driver failed" => (),
+            other => panic!("expected the daemon's error, got {:?}", other)
+        }
+        assert!(outputs.next().is_none());
+    }
+
+    #[test]
+    fn log_stream_tty() {
+        use std::io::Cursor;
+        use common::{LogOutput, LogStream};
+        let payload = Vec::from(&b"\x01\x00\x00\x00 is not a header with a tty"[..]);
+        let output : Vec<u8> = LogStream::new(Cursor::new(payload.clone()), true)
+            .flat_map(|output| output.unwrap().into_bytes())
+            .collect();
+        assert!(output == payload);
+        assert!(LogStream::new(Cursor::new(payload), true).all(|output| match output {
+            Ok(LogOutput::Console(_)) => true,
+            _ => false
+        }));
+    }
+
+    #[test]
+    fn log_stream_truncated() {
+        use std::io::Cursor;
+        use common::LogStream;
+        use error::DockerError;
+        let mut payload = frame(1, b"complete");
+        payload.extend_from_slice(&frame(2, b"cut off")[..10]);
+
+        let mut outputs = LogStream::new(Cursor::new(payload), false);
+        assert!(outputs.next().unwrap().is_ok());
+        match outputs.next() {
+            Some(Err(DockerError::Docker(_))) => (),
+            other => panic!("expected a truncated frame, got {:?}", other)
+        }
+        assert!(outputs.next().is_none());
+    }
+
+    #[test]
+    fn log_lines() {
+        use std::io::Cursor;
+        use common::{LogLines, LogOutput, LogStream};
+        let mut payload = frame(1, b"2017-04-12T09:21:04.178478373Z first ");
+        payload.extend(frame(2, b"2017-04-12T09:21:04.200000000Z oops\n"));
+        payload.extend(frame(1, b"line\n2017-04-12T09:21:05.000000000Z second\n"));
+        payload.extend(frame(1, b"2017-04-12T09:21:06.000000000Z no newline"));
+
+        let lines : Vec<_> = LogLines::new(LogStream::new(Cursor::new(payload), false), true)
+            .map(|line| line.unwrap())
+            .collect();
+        assert!(lines.len() == 4);
+        assert!(lines[0].timestamp == Some(String::from("2017-04-12T09:21:04.200000000Z")));
+        assert!(lines[0].output == LogOutput::StdErr(Vec::from(&b"oops\n"[..])));
+        assert!(lines[1].timestamp == Some(String::from("2017-04-12T09:21:04.178478373Z")));
+        assert!(lines[1].output == LogOutput::StdOut(Vec::from(&b"first line\n"[..])));
+        assert!(lines[2].output == LogOutput::StdOut(Vec::from(&b"second\n"[..])));
+        assert!(lines[3].output == LogOutput::StdOut(Vec::from(&b"no newline"[..])));
 
-    Ok(())    
+        let plain : Vec<_> = LogLines::new(LogStream::new(Cursor::new(&b"a b\nc"[..]), true), false)
+            .map(|line| line.unwrap())
+            .collect();
+        assert!(plain.len() == 2);
+        assert!(plain[0].timestamp.is_none());
+        assert!(plain[0].output == LogOutput::Console(Vec::from(&b"a b\n"[..])));
+    }
 }
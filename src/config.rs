@@ -0,0 +1,421 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+use std::env;
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use context::{current_context, Context, DEFAULT_CONTEXT};
+use error::DockerError;
+
+pub const DEFAULT_SOCKET: &'static str = "/var/run/docker.sock";
+pub const DEFAULT_API_VERSION: &'static str = "1.27";
+// Newest API version the schemas in this crate are written against
+pub const MAX_API_VERSION: &'static str = "1.35";
+pub const DEFAULT_PORT: u16 = 2375;
+pub const DEFAULT_TLS_PORT: u16 = 2376;
+
+// Where the daemon is listening
+#[derive(Debug, Clone, PartialEq)]
+pub enum Host {
+    // Path of a unix domain socket
+    Unix(String),
+
+    // host:port of a tcp endpoint
+    Tcp(String),
+
+    // [user@]host[:port] of a machine the daemon is reached on
+    // through `ssh ... docker system dial-stdio`
+    Ssh(String)
+}
+
+// Client certificates and verification settings for tcp hosts
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    // CA bundle the daemon certificate is checked against
+    pub ca: Option<PathBuf>,
+
+    // Client certificate and key presented to the daemon
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+
+    // Check the daemon certificate against the CA
+    pub verify: bool,
+
+    // Check that the daemon certificate matches the host name
+    pub verify_hostname: bool,
+
+    // Name sent as SNI and checked against the daemon certificate
+    // instead of the host we connect to
+    pub server_name: Option<String>
+}
+
+impl TlsConfig {
+    pub fn new(ca: &Path, cert: &Path, key: &Path) -> TlsConfig {
+        TlsConfig {
+            ca: Some(ca.to_path_buf()),
+            cert: Some(cert.to_path_buf()),
+            key: Some(key.to_path_buf()),
+            verify: true,
+            verify_hostname: true,
+            server_name: None
+        }
+    }
+
+    // Picks up ca.pem, cert.pem and key.pem from a directory, the
+    // same layout `docker-machine` and `DOCKER_CERT_PATH` use
+    pub fn from_cert_path(path: &Path, verify: bool) -> TlsConfig {
+        let existing = |name: &str| {
+            let file = path.join(name);
+            if file.exists() { Some(file) } else { None }
+        };
+
+        TlsConfig {
+            ca: existing("ca.pem"),
+            cert: existing("cert.pem"),
+            key: existing("key.pem"),
+            verify: verify,
+            verify_hostname: verify,
+            server_name: None
+        }
+    }
+
+    pub fn verify(mut self, verify: bool) -> TlsConfig {
+        self.verify = verify;
+        self
+    }
+
+    pub fn verify_hostname(mut self, verify: bool) -> TlsConfig {
+        self.verify_hostname = verify;
+        self
+    }
+
+    pub fn server_name(mut self, name: &str) -> TlsConfig {
+        self.server_name = Some(String::from(name));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub host: Host,
+    pub api_version: String,
+    pub tls: Option<TlsConfig>,
+
+    // Time allowed to establish the connection
+    pub connect_timeout: Option<Duration>,
+
+    // Abort when the daemon sends nothing for this long. Streamed and
+    // hijacked responses are left alone, they may be quiet for good.
+    pub read_timeout: Option<Duration>,
+
+    pub user_agent: String,
+
+    // Sent along with every request
+    pub headers: Vec<(String, String)>,
+
+    // Program run to reach ssh hosts
+    pub ssh_command: String
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            host: Host::Unix(String::from(DEFAULT_SOCKET)),
+            api_version: String::from(DEFAULT_API_VERSION),
+            tls: None,
+            connect_timeout: None,
+            read_timeout: None,
+            user_agent: format!("docker-rust/{}", env!("CARGO_PKG_VERSION")),
+            headers: Vec::new(),
+            ssh_command: String::from("ssh")
+        }
+    }
+}
+
+impl Config {
+    // Talks to a daemon listening on tcp, over TLS when `tls` is given
+    pub fn tcp(address: &str, tls: Option<TlsConfig>) -> Config {
+        let port = if tls.is_some() { DEFAULT_TLS_PORT } else { DEFAULT_PORT };
+        Config {
+            host: Host::Tcp(with_port(address, port)),
+            tls: tls,
+            ..Config::default()
+        }
+    }
+
+    // Reads DOCKER_HOST, DOCKER_API_VERSION, DOCKER_TLS_VERIFY,
+    // DOCKER_TLS and DOCKER_CERT_PATH like the docker cli does. Without
+    // DOCKER_HOST the context from DOCKER_CONTEXT or `config.json` is
+    // used.
+    pub fn from_env() -> Result<Config, DockerError> {
+        Config::from_vars(|name| env::var(name).ok())
+    }
+
+    pub fn from_vars<F>(var: F) -> Result<Config, DockerError>
+        where F: Fn(&str) -> Option<String>
+    {
+        match current_context(&var)? {
+            Some(name) => Config::from_context_vars(name.as_str(), var),
+            None => Config::from_host_vars(var)
+        }
+    }
+
+    // Talks to the daemon of a docker cli context
+    pub fn from_context(name: &str) -> Result<Config, DockerError> {
+        Config::from_context_vars(name, |name| env::var(name).ok())
+    }
+
+    pub fn from_context_vars<F>(name: &str, var: F) -> Result<Config, DockerError>
+        where F: Fn(&str) -> Option<String>
+    {
+        if name == DEFAULT_CONTEXT {
+            return Config::from_host_vars(var);
+        }
+
+        let mut config = Context::load(name, &var)?.config()?;
+        config.api_version = api_version(&var).unwrap_or(config.api_version);
+        Ok(config)
+    }
+
+    fn from_host_vars<F>(var: F) -> Result<Config, DockerError>
+        where F: Fn(&str) -> Option<String>
+    {
+        let var = |name: &str| var(name).and_then(|value| {
+            if value.is_empty() { None } else { Some(value) }
+        });
+
+        let verify = var("DOCKER_TLS_VERIFY").is_some();
+        let tls = verify || var("DOCKER_TLS").is_some();
+
+        let mut config = Config::default();
+        if let Some(version) = api_version(&var) {
+            config.api_version = version;
+        }
+
+        if let Some(host) = var("DOCKER_HOST") {
+            let (host, https) = parse_host(host.as_str(), tls)?;
+            config.host = host;
+            if https {
+                let cert_path = match var("DOCKER_CERT_PATH") {
+                    Some(path) => PathBuf::from(path),
+                    None => var("HOME").map(|home| Path::new(&home).join(".docker"))
+                                       .unwrap_or(PathBuf::from(".docker"))
+                };
+                config.tls = Some(TlsConfig::from_cert_path(&cert_path, verify));
+            }
+        }
+
+        Ok(config)
+    }
+
+    // Base url every endpoint path gets appended to
+    pub fn base_url(&self) -> String {
+        format!("{}v{}/", self.host_url(), self.api_version)
+    }
+
+    // Url of the daemon without the version prefix
+    pub fn host_url(&self) -> String {
+        match self.host {
+            Host::Unix(_) | Host::Ssh(_) => String::from("http://localhost/"),
+            Host::Tcp(ref address) => match self.tls {
+                Some(TlsConfig { server_name: Some(ref name), .. }) =>
+                    format!("https://{}:{}/", name, port(address)),
+                Some(_) => format!("https://{}/", address),
+                None => format!("http://{}/", address)
+            }
+        }
+    }
+
+    // When the server name is overridden the url carries that name,
+    // curl has to be told which address it actually resolves to
+    pub fn resolve_entry(&self) -> Result<Option<String>, DockerError> {
+        let (address, name) = match (&self.host, &self.tls) {
+            (&Host::Tcp(ref address), &Some(TlsConfig { server_name: Some(ref name), .. })) =>
+                (address, name),
+            _ => return Ok(None)
+        };
+
+        let resolved = address.as_str().to_socket_addrs()
+            .map_err(|error| DockerError::Docker(format!("could not resolve {}: {}", address, error)))?
+            .next()
+            .ok_or(DockerError::Docker(format!("could not resolve {}", address)))?;
+
+        Ok(Some(format!("{}:{}:{}", name, resolved.port(), resolved.ip())))
+    }
+}
+
+// DOCKER_API_VERSION without the optional leading `v`
+fn api_version<F>(var: F) -> Option<String>
+    where F: Fn(&str) -> Option<String>
+{
+    var("DOCKER_API_VERSION").and_then(|version| {
+        let version = if version.starts_with('v') { &version[1..] } else { &version[..] };
+        if version.is_empty() { None } else { Some(String::from(version)) }
+    })
+}
+
+// Parses a DOCKER_HOST value, returns the host and whether tls
+// should be used to talk to it
+pub fn parse_host(value: &str, tls: bool) -> Result<(Host, bool), DockerError> {
+    let (scheme, rest) = match value.find("://") {
+        Some(index) => (&value[..index], &value[index + 3..]),
+        None if value.starts_with('/') => ("unix", value),
+        None => ("tcp", value)
+    };
+
+    match scheme {
+        "unix" => {
+            if rest.is_empty() {
+                Ok((Host::Unix(String::from(DEFAULT_SOCKET)), false))
+            } else {
+                Ok((Host::Unix(String::from(rest)), false))
+            }
+        }
+        "tcp" | "http" | "https" => {
+            let https = scheme == "https" || (scheme == "tcp" && tls);
+            let address = if rest.ends_with('/') { &rest[..rest.len() - 1] } else { rest };
+            if address.is_empty() {
+                return Err(DockerError::Docker(format!("invalid DOCKER_HOST: {}", value)));
+            }
+
+            let port = if https { DEFAULT_TLS_PORT } else { DEFAULT_PORT };
+            Ok((Host::Tcp(with_port(address, port)), https))
+        }
+        "ssh" => {
+            let destination = if rest.ends_with('/') { &rest[..rest.len() - 1] } else { rest };
+            if destination.is_empty() || destination.contains('/') {
+                return Err(DockerError::Docker(format!("invalid DOCKER_HOST: {}", value)));
+            }
+            Ok((Host::Ssh(String::from(destination)), false))
+        }
+        _ => Err(DockerError::Docker(format!("unsupported DOCKER_HOST: {}", value)))
+    }
+}
+
+fn version_parts(version: &str) -> Vec<u32> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+// Compares API versions numerically, "1.9" is older than "1.27"
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    version_parts(a).cmp(&version_parts(b))
+}
+
+pub fn has_port(address: &str) -> bool {
+    address.rfind(':').map_or(false, |index| !address[index..].contains(']'))
+}
+
+fn with_port(address: &str, port: u16) -> String {
+    if has_port(address) {
+        String::from(address)
+    } else {
+        format!("{}:{}", address, port)
+    }
+}
+
+pub fn port(address: &str) -> &str {
+    match address.rfind(':') {
+        Some(index) => &address[index + 1..],
+        None => ""
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use config::{Config, Host};
+
+    fn from(vars: &[(&str, &str)]) -> Config {
+        let vars : HashMap<String, String> = vars.iter()
+            .map(|&(k, v)| (String::from(k), String::from(v)))
+            .collect();
+        Config::from_vars(|name| vars.get(name).cloned()).unwrap()
+    }
+
+    #[test]
+    fn defaults_to_local_socket() {
+        let config = from(&[]);
+        assert!(config.host == Host::Unix(String::from("/var/run/docker.sock")));
+        assert!(config.base_url() == "http://localhost/v1.27/");
+        assert!(config.tls.is_none());
+    }
+
+    #[test]
+    fn unix_host_and_version() {
+        let config = from(&[("DOCKER_HOST", "unix:///run/user/1000/docker.sock"),
+                            ("DOCKER_API_VERSION", "1.25")]);
+        assert!(config.host == Host::Unix(String::from("/run/user/1000/docker.sock")));
+        assert!(config.base_url() == "http://localhost/v1.25/");
+    }
+
+    #[test]
+    fn ssh_host() {
+        let config = from(&[("DOCKER_HOST", "ssh://builder@build-host:2222")]);
+        assert!(config.host == Host::Ssh(String::from("builder@build-host:2222")));
+        assert!(config.tls.is_none());
+        assert!(Config::from_vars(|name| if name == "DOCKER_HOST" { Some(String::from("ssh://")) } else { None }).is_err());
+    }
+
+    #[test]
+    fn tcp_host() {
+        let config = from(&[("DOCKER_HOST", "tcp://10.0.0.1")]);
+        assert!(config.host == Host::Tcp(String::from("10.0.0.1:2375")));
+        assert!(config.base_url() == "http://10.0.0.1:2375/v1.27/");
+
+        let config = from(&[("DOCKER_HOST", "http://docker.local:4243")]);
+        assert!(config.host == Host::Tcp(String::from("docker.local:4243")));
+    }
+
+    #[test]
+    fn tcp_host_with_tls() {
+        let config = from(&[("DOCKER_HOST", "tcp://10.0.0.1"),
+                            ("DOCKER_TLS_VERIFY", "1"),
+                            ("DOCKER_CERT_PATH", "/nonexistent")]);
+        assert!(config.host == Host::Tcp(String::from("10.0.0.1:2376")));
+        assert!(config.base_url() == "https://10.0.0.1:2376/v1.27/");
+        assert!(config.tls.unwrap().verify);
+    }
+
+    #[test]
+    fn tls_server_name_override() {
+        use config::TlsConfig;
+        let tls = TlsConfig::from_cert_path(Path::new("/nonexistent"), true)
+            .server_name("docker.example.com");
+        let config = Config::tcp("127.0.0.1", Some(tls));
+        assert!(config.base_url() == "https://docker.example.com:2376/v1.27/");
+        assert!(config.resolve_entry().unwrap() ==
+                Some(String::from("docker.example.com:2376:127.0.0.1")));
+    }
+
+    #[test]
+    fn version_ordering() {
+        use std::cmp::Ordering;
+        use config::compare_versions;
+        assert!(compare_versions("1.9", "1.27") == Ordering::Less);
+        assert!(compare_versions("1.27", "1.27") == Ordering::Equal);
+        assert!(compare_versions("1.30", "1.27") == Ordering::Greater);
+    }
+
+    #[test]
+    fn invalid_host() {
+        assert!(Config::from_vars(|name| {
+            if name == "DOCKER_HOST" { Some(String::from("fd://")) } else { None }
+        }).is_err());
+    }
+}
@@ -13,12 +13,30 @@
 // limitations under the License.
 
 pub mod schema;
+mod options;
+
+use base64;
 
 use super::common::*;
 use super::Client;
 use super::error::DockerError;
+use super::transport::{HijackedStream, Request, StreamingResponse};
+
+pub use self::options::{ContainerListOptions, LogOptions};
+
+use self::schema::{Container, ContainerCreateConfig, ContainerCreateResponse, ContainerInspect,
+                   ExecConfig, ExecCreateResponse, ExecStartConfig, PathStat};
 
-use self::schema::Container;
+// `StartPeriod` of health checks was added with API 1.29
+const START_PERIOD_API_VERSION: &'static str = "1.29";
+
+// Logs up to a point in time were added with API 1.35
+const LOGS_UNTIL_API_VERSION: &'static str = "1.35";
+
+// Content types of output with the stdout and stderr frames and of
+// the plain output of containers with a tty
+const MULTIPLEXED_STREAM: &'static str = "application/vnd.docker.multiplexed-stream";
+const RAW_STREAM: &'static str = "application/vnd.docker.raw-stream";
 
 endpoint!(ContainersClient);
 
@@ -27,6 +45,227 @@ impl<'a> ContainersClient<'a> {
     pub fn all(&self) -> Result<Vec<Container>, DockerError> {
         get_vector(self.client, "containers/json")
     }
+
+    pub fn list(&self, options: &ContainerListOptions) -> Result<Vec<Container>, DockerError> {
+        let mut request = Request::new("GET", "containers/json");
+        request.query = options.query();
+        let response = self.client.execute(request)?;
+        from_json(response.text()?.as_str())
+    }
+
+    // Creates a container without starting it, the daemon picks a
+    // name when there is none
+    pub fn create(&self, name: Option<&str>, config: &ContainerCreateConfig) -> Result<ContainerCreateResponse, DockerError> {
+        let start_period = config.healthcheck.as_ref().and_then(|health| health.start_period);
+        if start_period.is_some() {
+            self.client.require_api_version(START_PERIOD_API_VERSION)?;
+        }
+
+        let mut request = Request::new("POST", "containers/create");
+        if let Some(name) = name {
+            request = request.with_query("name", name);
+        }
+        let response = self.client.execute(request.with_json(config)?)?;
+        from_json(response.text()?.as_str())
+    }
+
+    // Low-level information about a container, `size` also computes
+    // the size of its filesystem which can take a while
+    pub fn inspect(&self, id: &str, size: bool) -> Result<ContainerInspect, DockerError> {
+        let url = format!("containers/{}/json", id);
+        let response = self.client.execute(Request::new("GET", url.as_str()).with_query("size", flag(size)))?;
+        from_json(response.text()?.as_str())
+    }
+
+    // Starts a created or stopped container. Comes back with false
+    // when the container was already running.
+    pub fn start(&self, id: &str) -> Result<bool, DockerError> {
+        let url = format!("containers/{}/start", id);
+        changed(self.client.execute(Request::new("POST", url.as_str())))
+    }
+
+    // Stops a running container, it gets killed when it is still up
+    // after `timeout` seconds. Comes back with false when the
+    // container was already stopped.
+    pub fn stop(&self, id: &str, timeout: Option<u32>) -> Result<bool, DockerError> {
+        let url = format!("containers/{}/stop", id);
+        changed(self.client.execute(with_timeout(Request::new("POST", url.as_str()), timeout)))
+    }
+
+    pub fn restart(&self, id: &str, timeout: Option<u32>) -> Result<(), DockerError> {
+        let url = format!("containers/{}/restart", id);
+        self.client.execute(with_timeout(Request::new("POST", url.as_str()), timeout))?;
+
+        Ok(())
+    }
+
+    // Sends a signal, e.g. `SIGHUP` or `9`, the daemon defaults to
+    // SIGKILL. Fails with `Conflict` when the container isn't running.
+    pub fn kill(&self, id: &str, signal: Option<&str>) -> Result<(), DockerError> {
+        let url = format!("containers/{}/kill", id);
+        let mut request = Request::new("POST", url.as_str());
+        if let Some(signal) = signal {
+            request = request.with_query("signal", signal);
+        }
+        self.client.execute(request)?;
+
+        Ok(())
+    }
+
+    pub fn pause(&self, id: &str) -> Result<(), DockerError> {
+        let url = format!("containers/{}/pause", id);
+        self.client.execute(Request::new("POST", url.as_str()))?;
+
+        Ok(())
+    }
+
+    pub fn unpause(&self, id: &str) -> Result<(), DockerError> {
+        let url = format!("containers/{}/unpause", id);
+        self.client.execute(Request::new("POST", url.as_str()))?;
+
+        Ok(())
+    }
+
+    // Removes a container, `force` kills it first when it is running,
+    // `volumes` also removes its anonymous volumes and `link` only
+    // removes the link with that name instead of the container
+    pub fn remove(&self, id: &str, force: bool, volumes: bool, link: bool) -> Result<(), DockerError> {
+        let url = format!("containers/{}", id);
+        self.client.execute(Request::new("DELETE", url.as_str())
+                            .with_query("force", flag(force))
+                            .with_query("v", flag(volumes))
+                            .with_query("link", flag(link)))?;
+
+        Ok(())
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> Result<(), DockerError> {
+        let url = format!("containers/{}/rename", id);
+        self.client.execute(Request::new("POST", url.as_str()).with_query("name", name))?;
+
+        Ok(())
+    }
+
+    // Information about a file or directory inside the container
+    pub fn archive_stat(&self, id: &str, path: &str) -> Result<PathStat, DockerError> {
+        let url = format!("containers/{}/archive", id);
+        let response = self.client.execute(Request::new("HEAD", url.as_str()).with_query("path", path))?;
+        let stat = response.header("X-Docker-Container-Path-Stat")
+            .ok_or(DockerError::Docker(String::from("daemon sent no path stat")))?;
+        let stat = base64::decode(stat)
+            .map_err(|error| DockerError::Docker(format!("invalid path stat: {}", error)))?;
+
+        from_json(String::from_utf8(stat)?.as_str())
+    }
+
+    // Tar archive of a file or directory inside the container
+    pub fn get_archive(&self, id: &str, path: &str) -> Result<Vec<u8>, DockerError> {
+        let url = format!("containers/{}/archive", id);
+        let response = self.client.execute(Request::new("GET", url.as_str()).with_query("path", path))?;
+
+        Ok(response.body)
+    }
+
+    // Extracts a tar archive into a directory inside the container
+    pub fn put_archive(&self, id: &str, path: &str, archive: Vec<u8>) -> Result<(), DockerError> {
+        let url = format!("containers/{}/archive", id);
+        self.client.execute(Request::new("PUT", url.as_str())
+                            .with_query("path", path)
+                            .with_body("application/x-tar", archive))?;
+
+        Ok(())
+    }
+
+    // Connects to stdin, stdout and stderr of a running container.
+    // Output of containers without a tty is multiplexed.
+    pub fn attach(&self, id: &str) -> Result<HijackedStream, DockerError> {
+        let url = format!("containers/{}/attach", id);
+        self.client.hijack(Request::new("POST", url.as_str())
+                           .with_query("stream", "1")
+                           .with_query("stdin", "1")
+                           .with_query("stdout", "1")
+                           .with_query("stderr", "1"))
+    }
+
+    // Sets up `config` to run in the running container `id`, it only
+    // runs once started with `exec_start`
+    pub fn exec_create(&self, id: &str, config: &ExecConfig) -> Result<ExecCreateResponse, DockerError> {
+        let url = format!("containers/{}/exec", id);
+        let response = self.client.execute(Request::new("POST", url.as_str()).with_json(config)?)?;
+        from_json(response.text()?.as_str())
+    }
+
+    // Runs the exec instance `id` and connects to the streams it was
+    // created to attach to. Output is multiplexed unless `tty`, which
+    // has to be what the exec was created with.
+    pub fn exec_start(&self, id: &str, tty: bool) -> Result<HijackedStream, DockerError> {
+        let url = format!("exec/{}/start", id);
+        let config = ExecStartConfig { detach: false, tty: tty };
+        self.client.hijack(Request::new("POST", url.as_str()).with_json(&config)?)
+    }
+
+    // Output of a container split into stdout and stderr. With
+    // `follow` the stream stays open and ends once the container stops.
+    pub fn logs(&self, id: &str, options: &LogOptions) -> Result<LogStream, DockerError> {
+        if options.has_until() {
+            self.client.require_api_version(LOGS_UNTIL_API_VERSION)?;
+        }
+
+        let url = format!("containers/{}/logs", id);
+        let mut request = Request::new("GET", url.as_str());
+        request.query = options.query();
+        let response = self.client.execute_stream(request)?;
+
+        // daemons since API 1.42 tell whether the output is multiplexed.
+        // Older ones send no content type or whatever got sniffed from
+        // the first bytes, only the caller knows then.
+        let content_type = response.header("Content-Type").unwrap_or("");
+        let tty = if content_type.starts_with(MULTIPLEXED_STREAM) {
+            false
+        } else if content_type.starts_with(RAW_STREAM) {
+            true
+        } else {
+            match options.tty_hint() {
+                Some(tty) => tty,
+                None => return Err(DockerError::Docker(format!(
+                    "the daemon doesn't say if the logs of {} are multiplexed, set LogOptions::tty", id)))
+            }
+        };
+        Ok(LogStream::new(response, tty))
+    }
+
+    // Like `logs` but line by line, with the timestamps split off when
+    // `options` asks for them
+    pub fn log_lines(&self, id: &str, options: &LogOptions) -> Result<LogLines, DockerError> {
+        Ok(LogLines::new(self.logs(id, options)?, options.has_timestamps()))
+    }
+
+    // Tar archive of the container's filesystem, read as it arrives
+    // instead of being held in memory
+    pub fn export(&self, id: &str) -> Result<StreamingResponse, DockerError> {
+        get_stream(self.client, format!("containers/{}/export", id).as_str())
+    }
+}
+
+fn with_timeout(request: Request, timeout: Option<u32>) -> Request {
+    match timeout {
+        Some(timeout) => request.with_query("t", timeout.to_string().as_str()),
+        None => request
+    }
+}
+
+fn flag(value: bool) -> &'static str {
+    if value { "1" } else { "0" }
+}
+
+// The daemon answers 304 when the container already is in the
+// requested state, which isn't a failure for start and stop
+fn changed<T>(result: Result<T, DockerError>) -> Result<bool, DockerError> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(DockerError::NotModified { .. }) => Ok(false),
+        Err(error) => Err(error)
+    }
 }
 
 
@@ -34,12 +273,489 @@ impl<'a> ContainersClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use containers::ContainersClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "Names": ["/web"], "State": "running"}));
+        daemon.add_container(&json!({"Id": "def", "Names": ["/db"], "State": "running"}));
+        let client = daemon.client();
         let container_client = ContainersClient::new(&client);
         let containers = container_client.all();
         assert!(containers.is_ok());
         assert!(containers.unwrap().len()>1);
     }
+
+    #[test]
+    fn archive() {
+        use containers::ContainersClient;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc"}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let archive = vec![0u8, 1, 2, 255];
+        container_client.put_archive("abc", "/tmp", archive.clone()).unwrap();
+        assert!(container_client.get_archive("abc", "/tmp").unwrap() == archive);
+
+        let stat = container_client.archive_stat("abc", "/tmp").unwrap();
+        assert!(stat.name == "tmp");
+        assert!(stat.size == 4);
+
+        match container_client.get_archive("missing", "/tmp") {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+
+        let put = daemon.requests().into_iter().find(|request| request.method == "PUT").unwrap();
+        assert!(put.query == vec![(String::from("path"), String::from("/tmp"))]);
+        assert!(put.header("Content-Type") == Some("application/x-tar"));
+    }
+
+    #[test]
+    fn export() {
+        use std::io::Read;
+        use containers::ContainersClient;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.respond("GET", "/containers/abc/export", 200, "not really a tar");
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let mut export = container_client.export("abc").unwrap();
+        let mut archive = String::new();
+        export.read_to_string(&mut archive).unwrap();
+        assert!(archive == "not really a tar");
+
+        match container_client.export("missing") {
+            Err(DockerError::NotFound { .. }) => (),
+            Err(other) => panic!("expected not found, got {:?}", other),
+            Ok(_) => panic!("expected not found")
+        }
+    }
+
+    #[test]
+    fn attach() {
+        use std::io::{Read, Write};
+        use containers::ContainersClient;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc"}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        // the mock's containers echo their input
+        let mut stream = container_client.attach("abc").unwrap();
+        assert!(stream.status == 101);
+        stream.write_all(b"echo hello\n").unwrap();
+        stream.input.close().unwrap();
+        let mut output = String::new();
+        stream.read_to_string(&mut output).unwrap();
+        assert!(output == "echo hello\n");
+
+        match container_client.attach("missing") {
+            Err(DockerError::NotFound { .. }) => (),
+            Err(other) => panic!("expected not found, got {:?}", other),
+            Ok(_) => panic!("expected not found")
+        }
+    }
+
+    #[test]
+    fn exec() {
+        use std::io::{Read, Write};
+        use containers::ContainersClient;
+        use containers::schema::ExecConfig;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "running"}));
+        daemon.add_container(&json!({"Id": "old", "State": "exited"}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let mut config = ExecConfig::new(&["cat"]);
+        config.attach_stdin = Some(true);
+        config.attach_stdout = Some(true);
+        config.tty = Some(true);
+        let exec = container_client.exec_create("abc", &config).unwrap();
+        let body : ::serde_json::Value = ::serde_json::from_slice(&daemon.requests()[0].body.clone().unwrap()).unwrap();
+        assert!(body == json!({"AttachStdin": true, "AttachStdout": true, "Tty": true, "Cmd": ["cat"]}));
+
+        // the mock's execs echo their input like `cat`
+        let mut stream = container_client.exec_start(exec.id.as_str(), true).unwrap();
+        assert!(stream.status == 101);
+        stream.write_all(b"hello\n").unwrap();
+        stream.input.close().unwrap();
+        let mut output = String::new();
+        stream.read_to_string(&mut output).unwrap();
+        assert!(output == "hello\n");
+        let request = daemon.requests().pop().unwrap();
+        assert!(request.path.ends_with(format!("/exec/{}/start", exec.id).as_str()));
+        assert!(request.header("Upgrade") == Some("tcp"));
+        assert!(request.body == Some(Vec::from(&br#"{"Detach":false,"Tty":true}"#[..])));
+
+        match container_client.exec_create("old", &config) {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+        match container_client.exec_start("missing", false) {
+            Err(DockerError::NotFound { .. }) => (),
+            Err(other) => panic!("expected not found, got {:?}", other),
+            Ok(_) => panic!("expected not found")
+        }
+    }
+
+    #[test]
+    fn create() {
+        use std::collections::HashMap;
+        use containers::ContainersClient;
+        use containers::schema::{ContainerCreateConfig, EmptyObject, HostConfig, PortBinding, RestartPolicy, Ulimit};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1", "RepoTags": ["nginx:latest"]}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let mut config = ContainerCreateConfig::new("nginx:latest");
+        config.cmd = Some(vec![String::from("nginx"), String::from("-g"), String::from("daemon off;")]);
+        config.env = Some(vec![String::from("MODE=test")]);
+        config.exposed_ports = Some(vec![(String::from("80/tcp"), EmptyObject {})].into_iter().collect());
+        let mut port_bindings = HashMap::new();
+        port_bindings.insert(String::from("80/tcp"), vec![PortBinding {
+            host_ip: None,
+            host_port: Some(String::from("8080"))
+        }]);
+        config.host_config = Some(HostConfig {
+            port_bindings: Some(port_bindings),
+            restart_policy: Some(RestartPolicy { name: Some(String::from("on-failure")), maximum_retry_count: Some(3) }),
+            memory: Some(64 * 1024 * 1024),
+            cap_drop: Some(vec![String::from("ALL")]),
+            ulimits: Some(vec![Ulimit { name: Some(String::from("nofile")), soft: Some(1024), hard: Some(2048) }]),
+            ..HostConfig::default()
+        });
+
+        let created = container_client.create(Some("web"), &config).unwrap();
+        assert!(created.id.len() == 64);
+        assert!(created.warnings == Some(Vec::new()));
+        assert!(container_client.inspect(created.id.as_str(), false).is_ok());
+
+        let request = daemon.requests().into_iter().find(|request| request.method == "POST").unwrap();
+        assert!(request.query == vec![(String::from("name"), String::from("web"))]);
+        let body : ::serde_json::Value = ::serde_json::from_slice(&request.body.unwrap()).unwrap();
+        assert!(body["ExposedPorts"] == json!({"80/tcp": {}}));
+        assert!(body["HostConfig"]["PortBindings"] == json!({"80/tcp": [{"HostPort": "8080"}]}));
+        assert!(body["HostConfig"]["RestartPolicy"] == json!({"Name": "on-failure", "MaximumRetryCount": 3}));
+        assert!(body.get("Entrypoint").is_none());
+
+        match container_client.create(Some("web"), &config) {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+        match container_client.create(None, &ContainerCreateConfig::new("missing:latest")) {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn create_with_start_period() {
+        use containers::schema::{ContainerCreateConfig, HealthConfig};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1", "RepoTags": ["nginx:latest"]}));
+
+        let mut config = ContainerCreateConfig::new("nginx:latest");
+        config.healthcheck = Some(HealthConfig {
+            test: Some(vec![String::from("CMD"), String::from("true")]),
+            start_period: Some(5000000000),
+            ..HealthConfig::default()
+        });
+        match daemon.client().containers().create(None, &config) {
+            Err(DockerError::UnsupportedApiVersion { ref required, .. }) if required == "1.29" => (),
+            other => panic!("expected an unsupported version, got {:?}", other)
+        }
+        assert!(daemon.requests().is_empty());
+
+        daemon.set_api_version("1.29");
+        let mut client = daemon.client();
+        client.negotiate_version().unwrap();
+        assert!(client.api_version() == "1.29");
+        assert!(client.containers().create(None, &config).is_ok());
+    }
+
+    #[test]
+    fn lifecycle() {
+        use containers::{ContainerListOptions, ContainersClient};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "Names": ["/web"], "State": "created"}));
+        daemon.add_container(&json!({"Id": "def", "Names": ["/db"], "State": "created"}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+        let state = |id: &str| {
+            container_client.inspect(id, false).unwrap().state.and_then(|state| state.status)
+        };
+
+        assert!(container_client.start("abc").unwrap());
+        assert!(!container_client.start("abc").unwrap());
+        assert!(state("abc") == Some(String::from("running")));
+
+        container_client.pause("abc").unwrap();
+        assert!(state("abc") == Some(String::from("paused")));
+        match container_client.pause("abc") {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+        container_client.unpause("abc").unwrap();
+
+        assert!(container_client.stop("abc", Some(5)).unwrap());
+        assert!(!container_client.stop("abc", None).unwrap());
+        assert!(state("abc") == Some(String::from("exited")));
+        match container_client.kill("abc", Some("SIGHUP")) {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+
+        container_client.restart("abc", Some(1)).unwrap();
+        container_client.kill("abc", Some("SIGKILL")).unwrap();
+        assert!(state("abc") == Some(String::from("exited")));
+
+        container_client.rename("abc", "frontend").unwrap();
+        match container_client.rename("abc", "db") {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+        assert!(container_client.inspect("abc", false).unwrap().name == Some(String::from("/frontend")));
+
+        container_client.start("def").unwrap();
+        match container_client.remove("def", false, false, false) {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+        container_client.remove("def", true, true, false).unwrap();
+        container_client.remove("abc", false, false, false).unwrap();
+        assert!(container_client.list(&ContainerListOptions::new().all(true)).unwrap().is_empty());
+        match container_client.start("abc") {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+
+        let requests = daemon.requests();
+        let stop = requests.iter().find(|request| request.path.ends_with("/stop")).unwrap();
+        assert!(stop.query == vec![(String::from("t"), String::from("5"))]);
+        let remove = requests.iter().filter(|request| request.method == "DELETE").nth(1).unwrap();
+        assert!(remove.query == vec![(String::from("force"), String::from("1")),
+                                     (String::from("v"), String::from("1")),
+                                     (String::from("link"), String::from("0"))]);
+    }
+
+    #[test]
+    fn inspect() {
+        use containers::ContainersClient;
+        use containers::schema::ContainerCreateConfig;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1", "RepoTags": ["redis:latest"]}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let mut config = ContainerCreateConfig::new("redis:latest");
+        config.env = Some(vec![String::from("MODE=test")]);
+        let id = container_client.create(Some("cache"), &config).unwrap().id;
+
+        let created = container_client.inspect(id.as_str(), false).unwrap();
+        assert!(created.id == id);
+        assert!(created.name == Some(String::from("/cache")));
+        let state = created.state.unwrap();
+        assert!(state.status == Some(String::from("created")));
+        assert!(state.running == Some(false));
+        assert!(created.config.unwrap().env == Some(vec![String::from("MODE=test")]));
+
+        container_client.start(id.as_str()).unwrap();
+        let state = container_client.inspect(id.as_str(), true).unwrap().state.unwrap();
+        assert!(state.running == Some(true));
+        assert!(state.pid != Some(0));
+
+        let request = daemon.requests().pop().unwrap();
+        assert!(request.query == vec![(String::from("size"), String::from("1"))]);
+
+        match container_client.inspect("missing", false) {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn list_options() {
+        use containers::ContainerListOptions;
+        let options = ContainerListOptions::new()
+            .all(true)
+            .limit(5)
+            .status("exited")
+            .label("team=x")
+            .label("tier")
+            .exited(137);
+        assert!(options.query() == vec![
+            (String::from("all"), String::from("1")),
+            (String::from("limit"), String::from("5")),
+            (String::from("filters"), String::from("{\"exited\":[\"137\"],\"label\":[\"team=x\",\"tier\"],\"status\":[\"exited\"]}"))
+        ]);
+        assert!(ContainerListOptions::new().query().is_empty());
+    }
+
+    #[test]
+    fn list() {
+        use containers::{ContainerListOptions, ContainersClient};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "a", "Names": ["/web"], "State": "running", "Labels": {"team": "x"}}));
+        daemon.add_container(&json!({"Id": "b", "Names": ["/job"], "State": "exited", "Labels": {"team": "x"}}));
+        daemon.add_container(&json!({"Id": "c", "Names": ["/other"], "State": "exited", "Labels": {"team": "y"}}));
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+        let ids = |options: &ContainerListOptions| -> Vec<String> {
+            container_client.list(options).unwrap().into_iter().filter_map(|container| container.id).collect()
+        };
+
+        assert!(ids(&ContainerListOptions::new()) == vec![String::from("a")]);
+        assert!(ids(&ContainerListOptions::new().all(true)).len() == 3);
+        assert!(ids(&ContainerListOptions::new().all(true).status("exited").label("team=x")) == vec![String::from("b")]);
+        assert!(ids(&ContainerListOptions::new().all(true).status("exited").status("running").label("team=x")).len() == 2);
+        assert!(ids(&ContainerListOptions::new().limit(1)) == vec![String::from("c")]);
+
+        daemon.respond("GET", "/containers/json", 400, "{\"message\":\"invalid filter 'bogus'\"}");
+        match container_client.list(&ContainerListOptions::new().filter("bogus", "1")) {
+            Err(DockerError::BadParameter { message }) => assert!(message == "invalid filter 'bogus'"),
+            other => panic!("expected a bad parameter, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn logs() {
+        use common::LogOutput;
+        use containers::{ContainersClient, LogOptions};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "exited", "Config": {"Image": "busybox", "Tty": false}}));
+        daemon.add_container(&json!({"Id": "tty", "State": "exited", "Config": {"Image": "busybox", "Tty": true}}));
+        daemon.add_log("abc", "stdout", "starting\n");
+        daemon.add_log("abc", "stderr", "warning\n");
+        daemon.add_log("abc", "stdout", "done\n");
+        daemon.add_log("tty", "stdout", "\u{1b}[1mprompt\u{1b}[0m\n");
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let outputs : Vec<LogOutput> = container_client.logs("abc", &LogOptions::new()).unwrap()
+            .map(|output| output.unwrap())
+            .collect();
+        assert!(outputs == vec![LogOutput::StdOut(Vec::from(&b"starting\n"[..])),
+                                LogOutput::StdErr(Vec::from(&b"warning\n"[..])),
+                                LogOutput::StdOut(Vec::from(&b"done\n"[..]))]);
+
+        let options = LogOptions::new().stderr(false).tail(1).timestamps(true).since(1483228800);
+        let lines : Vec<_> = container_client.log_lines("abc", &options).unwrap()
+            .map(|line| line.unwrap())
+            .collect();
+        assert!(lines.len() == 1);
+        assert!(lines[0].timestamp == Some(String::from("2017-01-01T00:00:02.000000000Z")));
+        assert!(lines[0].output == LogOutput::StdOut(Vec::from(&b"done\n"[..])));
+        let request = daemon.requests().into_iter().rev().find(|request| request.path.ends_with("/logs")).unwrap();
+        assert!(request.query == vec![(String::from("stdout"), String::from("1")),
+                                      (String::from("stderr"), String::from("0")),
+                                      (String::from("since"), String::from("1483228800")),
+                                      (String::from("tail"), String::from("1")),
+                                      (String::from("timestamps"), String::from("1"))]);
+
+        let console : Vec<LogOutput> = container_client.logs("tty", &LogOptions::new()).unwrap()
+            .map(|output| output.unwrap())
+            .collect();
+        assert!(console == vec![LogOutput::Console(Vec::from(&b"\x1b[1mprompt\x1b[0m\n"[..]))]);
+
+        match container_client.logs("missing", &LogOptions::new()) {
+            Err(DockerError::NotFound { .. }) => (),
+            Err(other) => panic!("expected not found, got {:?}", other),
+            Ok(_) => panic!("expected not found")
+        }
+    }
+
+    #[test]
+    fn logs_of_older_daemons() {
+        use common::LogOutput;
+        use containers::{ContainersClient, LogOptions};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "exited", "Config": {"Image": "busybox", "Tty": false}}));
+        // no word on the framing, as before API 1.42
+        daemon.respond("GET", "/containers/abc/logs", 200, "\u{1}\u{0}\u{0}\u{0}\u{0}\u{0}\u{0}\u{3}hi\n");
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        match container_client.logs("abc", &LogOptions::new()) {
+            Err(DockerError::Docker(ref message)) if message.contains("LogOptions::tty") => (),
+            Err(other) => panic!("expected an unknown tty, got {:?}", other),
+            Ok(_) => panic!("expected an unknown tty")
+        }
+
+        let outputs : Vec<LogOutput> = container_client.logs("abc", &LogOptions::new().tty(false)).unwrap()
+            .map(|output| output.unwrap())
+            .collect();
+        assert!(outputs == vec![LogOutput::StdOut(Vec::from(&b"hi\n"[..]))]);
+        assert!(daemon.requests().iter().all(|request| request.path.ends_with("/logs")));
+    }
+
+    #[test]
+    fn logs_until() {
+        use containers::{ContainersClient, LogOptions};
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "exited"}));
+        daemon.add_log("abc", "stdout", "done\n");
+        let options = LogOptions::new().until(1483228800).tty(false);
+
+        match ContainersClient::new(&daemon.client()).logs("abc", &options) {
+            Err(DockerError::UnsupportedApiVersion { ref required, .. }) if required == "1.35" => (),
+            Err(other) => panic!("expected an unsupported version, got {:?}", other),
+            Ok(_) => panic!("expected an unsupported version")
+        }
+        assert!(daemon.requests().is_empty());
+
+        daemon.set_api_version("1.35");
+        let mut client = daemon.client();
+        client.negotiate_version().unwrap();
+        assert!(ContainersClient::new(&client).logs("abc", &options).unwrap().count() == 1);
+        let request = daemon.requests().pop().unwrap();
+        assert!(request.query.contains(&(String::from("until"), String::from("1483228800"))));
+    }
+
+    #[test]
+    fn follow_logs() {
+        use containers::{ContainersClient, LogOptions};
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "running"}));
+        daemon.add_log("abc", "stdout", "first\n");
+        let client = daemon.client();
+        let container_client = ContainersClient::new(&client);
+
+        let mut lines = container_client.log_lines("abc", &LogOptions::new().follow(true)).unwrap();
+        assert!(lines.next().unwrap().unwrap().output.as_bytes() == b"first\n");
+
+        daemon.add_log("abc", "stdout", "second\n");
+        assert!(lines.next().unwrap().unwrap().output.as_bytes() == b"second\n");
+
+        // the stream ends once the container has stopped
+        container_client.stop("abc", None).unwrap();
+        assert!(lines.next().is_none());
+    }
 }
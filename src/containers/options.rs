@@ -0,0 +1,284 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use serde_json;
+
+// Query of `GET /containers/json`, by default only running containers
+// are listed
+//
+//     let options = ContainerListOptions::new()
+//         .all(true)
+//         .status("exited")
+//         .label("team=x");
+//     let containers = client.containers().list(&options)?;
+#[derive(Clone, Debug, Default)]
+pub struct ContainerListOptions {
+    all: bool,
+    limit: Option<u32>,
+    size: bool,
+    // sorted, which keeps the query stable between calls
+    filters: BTreeMap<String, Vec<String>>
+}
+
+impl ContainerListOptions {
+    pub fn new() -> ContainerListOptions {
+        ContainerListOptions::default()
+    }
+
+    // Also lists containers that aren't running
+    pub fn all(mut self, all: bool) -> ContainerListOptions {
+        self.all = all;
+        self
+    }
+
+    // Only the `limit` most recently created containers, stopped ones
+    // included
+    pub fn limit(mut self, limit: u32) -> ContainerListOptions {
+        self.limit = Some(limit);
+        self
+    }
+
+    // Fills in `SizeRw` and `SizeRootFs`
+    pub fn size(mut self, size: bool) -> ContainerListOptions {
+        self.size = size;
+        self
+    }
+
+    // Adds a filter the daemon knows but there is no method for.
+    // Filters with different keys must all match, values of the same
+    // key match when any of them does.
+    pub fn filter(mut self, key: &str, value: &str) -> ContainerListOptions {
+        self.filters.entry(String::from(key)).or_insert_with(Vec::new).push(String::from(value));
+        self
+    }
+
+    // `created`, `restarting`, `running`, `removing`, `paused`,
+    // `exited` or `dead`
+    pub fn status(self, status: &str) -> ContainerListOptions {
+        self.filter("status", status)
+    }
+
+    // Either a label key or `key=value`
+    pub fn label(self, label: &str) -> ContainerListOptions {
+        self.filter("label", label)
+    }
+
+    pub fn name(self, name: &str) -> ContainerListOptions {
+        self.filter("name", name)
+    }
+
+    // Containers created from an image, given as name, id or
+    // `name:tag`, or from one of its descendants
+    pub fn ancestor(self, image: &str) -> ContainerListOptions {
+        self.filter("ancestor", image)
+    }
+
+    pub fn network(self, network: &str) -> ContainerListOptions {
+        self.filter("network", network)
+    }
+
+    // A volume name or a mount point inside the container
+    pub fn volume(self, volume: &str) -> ContainerListOptions {
+        self.filter("volume", volume)
+    }
+
+    // `starting`, `healthy`, `unhealthy` or `none`
+    pub fn health(self, health: &str) -> ContainerListOptions {
+        self.filter("health", health)
+    }
+
+    pub fn exited(self, code: i32) -> ContainerListOptions {
+        self.filter("exited", code.to_string().as_str())
+    }
+
+    // Containers created before or since the one with this id or name
+    pub fn before(self, container: &str) -> ContainerListOptions {
+        self.filter("before", container)
+    }
+
+    pub fn since(self, container: &str) -> ContainerListOptions {
+        self.filter("since", container)
+    }
+
+    // A port, e.g. `80` or `8080/tcp`, or a range like `8000-8080`
+    pub fn publish(self, port: &str) -> ContainerListOptions {
+        self.filter("publish", port)
+    }
+
+    pub fn expose(self, port: &str) -> ContainerListOptions {
+        self.filter("expose", port)
+    }
+
+    // The query parameters, defaults are left out
+    pub fn query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        if self.all {
+            query.push((String::from("all"), String::from("1")));
+        }
+        if let Some(limit) = self.limit {
+            query.push((String::from("limit"), limit.to_string()));
+        }
+        if self.size {
+            query.push((String::from("size"), String::from("1")));
+        }
+        if !self.filters.is_empty() {
+            // a map of strings can always be serialized
+            query.push((String::from("filters"), serde_json::to_string(&self.filters).unwrap()));
+        }
+        query
+    }
+}
+
+// Query of `GET /containers/{id}/logs`, stdout and stderr are both
+// included unless turned off
+//
+//     let options = LogOptions::new()
+//         .follow(true)
+//         .tail(100)
+//         .timestamps(true);
+//     for line in client.containers().log_lines("web", &options)? {
+//         ...
+//     }
+#[derive(Clone, Debug)]
+pub struct LogOptions {
+    stdout: bool,
+    stderr: bool,
+    follow: bool,
+    since: Option<i64>,
+    until: Option<i64>,
+    tail: Option<u32>,
+    timestamps: bool,
+    details: bool,
+    tty: Option<bool>
+}
+
+impl Default for LogOptions {
+    fn default() -> LogOptions {
+        LogOptions::new()
+    }
+}
+
+impl LogOptions {
+    pub fn new() -> LogOptions {
+        LogOptions {
+            stdout: true,
+            stderr: true,
+            follow: false,
+            since: None,
+            until: None,
+            tail: None,
+            timestamps: false,
+            details: false,
+            tty: None
+        }
+    }
+
+    pub fn stdout(mut self, stdout: bool) -> LogOptions {
+        self.stdout = stdout;
+        self
+    }
+
+    pub fn stderr(mut self, stderr: bool) -> LogOptions {
+        self.stderr = stderr;
+        self
+    }
+
+    // Keeps the stream open for new output until the container stops
+    pub fn follow(mut self, follow: bool) -> LogOptions {
+        self.follow = follow;
+        self
+    }
+
+    // Only output since or until a unix timestamp in seconds, `until`
+    // needs API 1.35
+    pub fn since(mut self, since: i64) -> LogOptions {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: i64) -> LogOptions {
+        self.until = Some(until);
+        self
+    }
+
+    // Only the last `lines` lines, all of them by default
+    pub fn tail(mut self, lines: u32) -> LogOptions {
+        self.tail = Some(lines);
+        self
+    }
+
+    // Puts the time of every line in front of it
+    pub fn timestamps(mut self, timestamps: bool) -> LogOptions {
+        self.timestamps = timestamps;
+        self
+    }
+
+    // Adds the extra attributes the log driver was configured with,
+    // e.g. labels or env vars, to every line
+    pub fn details(mut self, details: bool) -> LogOptions {
+        self.details = details;
+        self
+    }
+
+    // Whether the container has a tty, which decides if its output is
+    // split into stdout and stderr frames. Daemons before API 1.42
+    // don't say, their logs can only be read when this is set.
+    pub fn tty(mut self, tty: bool) -> LogOptions {
+        self.tty = Some(tty);
+        self
+    }
+
+    pub fn has_timestamps(&self) -> bool {
+        self.timestamps
+    }
+
+    pub fn has_until(&self) -> bool {
+        self.until.is_some()
+    }
+
+    pub fn tty_hint(&self) -> Option<bool> {
+        self.tty
+    }
+
+    // The query parameters, defaults are left out apart from the
+    // streams as the daemon wants at least one of them
+    pub fn query(&self) -> Vec<(String, String)> {
+        let flag = |name: &str| (String::from(name), String::from("1"));
+        let mut query = vec![
+            (String::from("stdout"), String::from(if self.stdout { "1" } else { "0" })),
+            (String::from("stderr"), String::from(if self.stderr { "1" } else { "0" }))
+        ];
+        if self.follow {
+            query.push(flag("follow"));
+        }
+        if let Some(since) = self.since {
+            query.push((String::from("since"), since.to_string()));
+        }
+        if let Some(until) = self.until {
+            query.push((String::from("until"), until.to_string()));
+        }
+        if let Some(tail) = self.tail {
+            query.push((String::from("tail"), tail.to_string()));
+        }
+        if self.timestamps {
+            query.push(flag("timestamps"));
+        }
+        if self.details {
+            query.push(flag("details"));
+        }
+        query
+    }
+}
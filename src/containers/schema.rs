@@ -14,16 +14,220 @@
 
 use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+// What the daemon does when the container exits
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RestartPolicy {
+   // `no`, `always`, `unless-stopped` or `on-failure`
+   #[serde(rename = "Name")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub name : Option<String>,
+
+   // Retries before giving up, only for `on-failure`
+   #[serde(rename = "MaximumRetryCount")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub maximum_retry_count : Option<i64>
+}
+
+// Host address a container port is published on
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PortBinding {
+   #[serde(rename = "HostIp")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub host_ip : Option<String>,
+
+   #[serde(rename = "HostPort")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub host_port : Option<String>
+}
+
+// A device of the host made available in the container
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct DeviceMapping {
+   #[serde(rename = "PathOnHost")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub path_on_host : Option<String>,
+
+   #[serde(rename = "PathInContainer")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub path_in_container : Option<String>,
+
+   // Combination of `r`, `w` and `m`
+   #[serde(rename = "CgroupPermissions")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cgroup_permissions : Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LogConfig {
+   // Logging driver, e.g. `json-file` or `syslog`
+   #[serde(rename = "Type")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub log_type : Option<String>,
+
+   #[serde(rename = "Config")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub config : Option<HashMap<String,String>>
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Ulimit {
+   #[serde(rename = "Name")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub name : Option<String>,
+
+   #[serde(rename = "Soft")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub soft : Option<i64>,
+
+   #[serde(rename = "Hard")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub hard : Option<i64>
+}
+
+// Container settings that depend on the host
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct HostConfig {
    #[serde(rename = "NetworkMode")]
    #[serde(skip_serializing_if = "Option::is_none")]
-   pub network_mode : Option<String>
+   pub network_mode : Option<String>,
+
+   // Volume bindings, `host-src:container-dest[:options]`
+   #[serde(rename = "Binds")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub binds : Option<Vec<String>>,
+
+   // Published ports by container port, e.g. `80/tcp`
+   #[serde(rename = "PortBindings")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub port_bindings : Option<HashMap<String,Vec<PortBinding>>>,
+
+   #[serde(rename = "PublishAllPorts")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub publish_all_ports : Option<bool>,
+
+   #[serde(rename = "RestartPolicy")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub restart_policy : Option<RestartPolicy>,
+
+   // Remove the container when it exits
+   #[serde(rename = "AutoRemove")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub auto_remove : Option<bool>,
+
+   #[serde(rename = "VolumesFrom")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub volumes_from : Option<Vec<String>>,
+
+   #[serde(rename = "Links")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub links : Option<Vec<String>>,
+
+   #[serde(rename = "Mounts")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mounts : Option<Vec<Mount>>,
+
+   // Memory limit in bytes
+   #[serde(rename = "Memory")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub memory : Option<i64>,
+
+   // Memory plus swap limit in bytes, -1 for unlimited swap
+   #[serde(rename = "MemorySwap")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub memory_swap : Option<i64>,
+
+   #[serde(rename = "MemoryReservation")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub memory_reservation : Option<i64>,
+
+   // CPU quota in units of 10^-9 CPUs
+   #[serde(rename = "NanoCpus")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub nano_cpus : Option<i64>,
+
+   #[serde(rename = "CpuShares")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cpu_shares : Option<i64>,
+
+   #[serde(rename = "CpuPeriod")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cpu_period : Option<i64>,
+
+   #[serde(rename = "CpuQuota")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cpu_quota : Option<i64>,
+
+   #[serde(rename = "CpusetCpus")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cpuset_cpus : Option<String>,
+
+   #[serde(rename = "PidsLimit")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub pids_limit : Option<i64>,
+
+   #[serde(rename = "OomKillDisable")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub oom_kill_disable : Option<bool>,
+
+   #[serde(rename = "ShmSize")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub shm_size : Option<i64>,
+
+   #[serde(rename = "CapAdd")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cap_add : Option<Vec<String>>,
+
+   #[serde(rename = "CapDrop")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cap_drop : Option<Vec<String>>,
+
+   #[serde(rename = "Privileged")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub privileged : Option<bool>,
+
+   #[serde(rename = "ReadonlyRootfs")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub readonly_rootfs : Option<bool>,
+
+   #[serde(rename = "Devices")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub devices : Option<Vec<DeviceMapping>>,
+
+   #[serde(rename = "LogConfig")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub log_config : Option<LogConfig>,
+
+   #[serde(rename = "SecurityOpt")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub security_opt : Option<Vec<String>>,
+
+   // Mount options by container path
+   #[serde(rename = "Tmpfs")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub tmpfs : Option<HashMap<String,String>>,
+
+   #[serde(rename = "Ulimits")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub ulimits : Option<Vec<Ulimit>>,
+
+   #[serde(rename = "Dns")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub dns : Option<Vec<String>>,
+
+   // Additional `/etc/hosts` lines, `hostname:IP`
+   #[serde(rename = "ExtraHosts")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub extra_hosts : Option<Vec<String>>,
+
+   // Run an init inside the container that reaps processes
+   #[serde(rename = "Init")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub init : Option<bool>
 }
 
 
 // IPAM configurations for the endpoint
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct EndpointSettingsIpamConfig {
    #[serde(rename = "IPv4Address")]
    #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,7 +244,7 @@ pub struct EndpointSettingsIpamConfig {
 
 
 // Configuration for a network endpoint.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct EndpointSettings {
    #[serde(rename = "IPAMConfig")]
    #[serde(skip_serializing_if = "Option::is_none")]
@@ -98,6 +302,38 @@ pub struct NetworkSettings {
    #[serde(rename = "Networks")]
    #[serde(skip_serializing_if = "Option::is_none")]
    pub networks : Option<HashMap<String,EndpointSettings>>,
+
+   // The remaining fields are only filled in by inspect
+
+   #[serde(rename = "Bridge")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub bridge : Option<String>,
+
+   #[serde(rename = "SandboxID")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub sandbox_id : Option<String>,
+
+   #[serde(rename = "SandboxKey")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub sandbox_key : Option<String>,
+
+   // Published ports by container port, e.g. `80/tcp`. Exposed but
+   // unpublished ports map to null.
+   #[serde(rename = "Ports")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub ports : Option<HashMap<String,Option<Vec<PortBinding>>>>,
+
+   #[serde(rename = "IPAddress")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub ip_address : Option<String>,
+
+   #[serde(rename = "Gateway")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub gateway : Option<String>,
+
+   #[serde(rename = "MacAddress")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mac_address : Option<String>,
 }
 
 // An open port on a container
@@ -297,3 +533,490 @@ pub struct Container {
    #[serde(skip_serializing_if = "Option::is_none")]
    pub mounts : Option<Vec<Mount>>
 }
+
+// Stat of a path inside a container, sent base64 encoded in the
+// `X-Docker-Container-Path-Stat` header of the archive endpoints
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PathStat {
+   #[serde(rename = "name")]
+   pub name : String,
+
+   #[serde(rename = "size")]
+   pub size : i64,
+
+   // File mode bits as Go's os.FileMode
+   #[serde(rename = "mode")]
+   pub mode : u32,
+
+   #[serde(rename = "mtime")]
+   pub mtime : String,
+
+   #[serde(rename = "linkTarget")]
+   pub link_target : String
+}
+
+// Serializes to `{}`, the value of exposed ports and volumes
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct EmptyObject {}
+
+// How the daemon checks that the container is healthy
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HealthConfig {
+   // `["CMD", args...]`, `["CMD-SHELL", command]` or `["NONE"]`
+   #[serde(rename = "Test")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub test : Option<Vec<String>>,
+
+   // Nanoseconds between checks
+   #[serde(rename = "Interval")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub interval : Option<i64>,
+
+   // Nanoseconds before a check counts as hung
+   #[serde(rename = "Timeout")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub timeout : Option<i64>,
+
+   // Failures in a row before the container is unhealthy
+   #[serde(rename = "Retries")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub retries : Option<i64>,
+
+   // Nanoseconds to wait before counting failures, needs API 1.29
+   #[serde(rename = "StartPeriod")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub start_period : Option<i64>
+}
+
+// Networks to connect the container to when it is created
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct NetworkingConfig {
+   // Endpoint settings by network name
+   #[serde(rename = "EndpointsConfig")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub endpoints_config : Option<HashMap<String,EndpointSettings>>
+}
+
+// Body of `POST /containers/create`, inspect also hands it back
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ContainerCreateConfig {
+   // Image to create the container from
+   #[serde(rename = "Image")]
+   pub image : String,
+
+   #[serde(rename = "Cmd")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub cmd : Option<Vec<String>>,
+
+   #[serde(rename = "Entrypoint")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub entrypoint : Option<Vec<String>>,
+
+   // `NAME=value` pairs
+   #[serde(rename = "Env")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub env : Option<Vec<String>>,
+
+   #[serde(rename = "Labels")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub labels : Option<HashMap<String,String>>,
+
+   // Ports by `port/protocol`, e.g. `80/tcp`
+   #[serde(rename = "ExposedPorts")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub exposed_ports : Option<HashMap<String,EmptyObject>>,
+
+   // Paths inside the container to create anonymous volumes for
+   #[serde(rename = "Volumes")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub volumes : Option<HashMap<String,EmptyObject>>,
+
+   #[serde(rename = "WorkingDir")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub working_dir : Option<String>,
+
+   #[serde(rename = "User")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub user : Option<String>,
+
+   #[serde(rename = "Hostname")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub hostname : Option<String>,
+
+   #[serde(rename = "Domainname")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub domainname : Option<String>,
+
+   #[serde(rename = "AttachStdin")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stdin : Option<bool>,
+
+   #[serde(rename = "AttachStdout")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stdout : Option<bool>,
+
+   #[serde(rename = "AttachStderr")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stderr : Option<bool>,
+
+   #[serde(rename = "Tty")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub tty : Option<bool>,
+
+   #[serde(rename = "OpenStdin")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub open_stdin : Option<bool>,
+
+   #[serde(rename = "StdinOnce")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub stdin_once : Option<bool>,
+
+   #[serde(rename = "StopSignal")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub stop_signal : Option<String>,
+
+   // Seconds to wait for the container to stop before killing it
+   #[serde(rename = "StopTimeout")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub stop_timeout : Option<i64>,
+
+   #[serde(rename = "Healthcheck")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub healthcheck : Option<HealthConfig>,
+
+   #[serde(rename = "NetworkDisabled")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub network_disabled : Option<bool>,
+
+   #[serde(rename = "HostConfig")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub host_config : Option<HostConfig>,
+
+   #[serde(rename = "NetworkingConfig")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub networking_config : Option<NetworkingConfig>
+}
+
+impl ContainerCreateConfig {
+    pub fn new(image: &str) -> ContainerCreateConfig {
+        ContainerCreateConfig {
+            image: String::from(image),
+            ..ContainerCreateConfig::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContainerCreateResponse {
+   #[serde(rename = "Id")]
+   pub id : String,
+
+   // e.g. about limits the kernel does not support
+   #[serde(rename = "Warnings")]
+   pub warnings : Option<Vec<String>>
+}
+
+// Command to run in a running container, interactive ones attach to
+// the streams they need
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ExecConfig {
+   #[serde(rename = "AttachStdin")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stdin : Option<bool>,
+
+   #[serde(rename = "AttachStdout")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stdout : Option<bool>,
+
+   #[serde(rename = "AttachStderr")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attach_stderr : Option<bool>,
+
+   // e.g. `ctrl-p,ctrl-q`
+   #[serde(rename = "DetachKeys")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub detach_keys : Option<String>,
+
+   #[serde(rename = "Tty")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub tty : Option<bool>,
+
+   // `KEY=value` pairs
+   #[serde(rename = "Env")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub env : Option<Vec<String>>,
+
+   #[serde(rename = "Cmd")]
+   pub cmd : Vec<String>,
+
+   #[serde(rename = "Privileged")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub privileged : Option<bool>,
+
+   // `user`, `user:group`, `uid` or `uid:gid`
+   #[serde(rename = "User")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub user : Option<String>
+}
+
+impl ExecConfig {
+    pub fn new(cmd: &[&str]) -> ExecConfig {
+        ExecConfig {
+            cmd: cmd.iter().map(|arg| String::from(*arg)).collect(),
+            ..ExecConfig::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecCreateResponse {
+   #[serde(rename = "Id")]
+   pub id : String
+}
+
+// Body of `exec/{id}/start`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecStartConfig {
+   #[serde(rename = "Detach")]
+   pub detach : bool,
+
+   // has to match the `tty` the exec was created with
+   #[serde(rename = "Tty")]
+   pub tty : bool
+}
+
+// Result of a single health check run
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HealthcheckResult {
+   #[serde(rename = "Start")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub start : Option<String>,
+
+   #[serde(rename = "End")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub end : Option<String>,
+
+   #[serde(rename = "ExitCode")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub exit_code : Option<i64>,
+
+   #[serde(rename = "Output")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub output : Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Health {
+   // `starting`, `healthy` or `unhealthy`
+   #[serde(rename = "Status")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub status : Option<String>,
+
+   // Number of consecutive failed checks
+   #[serde(rename = "FailingStreak")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub failing_streak : Option<i64>,
+
+   // The most recent checks, oldest first
+   #[serde(rename = "Log")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub log : Option<Vec<HealthcheckResult>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContainerState {
+   // `created`, `running`, `paused`, `restarting`, `removing`,
+   // `exited` or `dead`
+   #[serde(rename = "Status")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub status : Option<String>,
+
+   #[serde(rename = "Running")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub running : Option<bool>,
+
+   #[serde(rename = "Paused")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub paused : Option<bool>,
+
+   #[serde(rename = "Restarting")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub restarting : Option<bool>,
+
+   // Whether the last exit was caused by running out of memory
+   #[serde(rename = "OOMKilled")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub oom_killed : Option<bool>,
+
+   #[serde(rename = "Dead")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub dead : Option<bool>,
+
+   // Process id of the main process, 0 while not running
+   #[serde(rename = "Pid")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub pid : Option<i64>,
+
+   #[serde(rename = "ExitCode")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub exit_code : Option<i64>,
+
+   #[serde(rename = "Error")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub error : Option<String>,
+
+   // RFC 3339 timestamps, `0001-01-01T00:00:00Z` when it never happened
+   #[serde(rename = "StartedAt")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub started_at : Option<String>,
+
+   #[serde(rename = "FinishedAt")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub finished_at : Option<String>,
+
+   // Only there when the image or the container defines a health check
+   #[serde(rename = "Health")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub health : Option<Health>,
+}
+
+// A mount as the daemon has set it up, unlike `Mount` which is what
+// gets requested
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MountPoint {
+   #[serde(rename = "Type")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mount_type : Option<String>,
+
+   // Volume name, empty for bind mounts
+   #[serde(rename = "Name")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub name : Option<String>,
+
+   #[serde(rename = "Source")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub source : Option<String>,
+
+   #[serde(rename = "Destination")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub destination : Option<String>,
+
+   #[serde(rename = "Driver")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub driver : Option<String>,
+
+   #[serde(rename = "Mode")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mode : Option<String>,
+
+   #[serde(rename = "RW")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub rw : Option<bool>,
+
+   #[serde(rename = "Propagation")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub propagation : Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContainerInspect {
+   #[serde(rename = "Id")]
+   pub id : String,
+
+   // Always starts with a slash, e.g. `/web`
+   #[serde(rename = "Name")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub name : Option<String>,
+
+   #[serde(rename = "Created")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub created : Option<String>,
+
+   // Path and arguments of the main process
+   #[serde(rename = "Path")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub path : Option<String>,
+
+   #[serde(rename = "Args")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub args : Option<Vec<String>>,
+
+   #[serde(rename = "State")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub state : Option<ContainerState>,
+
+   // Id of the image the container was created from
+   #[serde(rename = "Image")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub image : Option<String>,
+
+   #[serde(rename = "ResolvConfPath")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub resolv_conf_path : Option<String>,
+
+   #[serde(rename = "HostnamePath")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub hostname_path : Option<String>,
+
+   #[serde(rename = "HostsPath")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub hosts_path : Option<String>,
+
+   #[serde(rename = "LogPath")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub log_path : Option<String>,
+
+   #[serde(rename = "RestartCount")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub restart_count : Option<i64>,
+
+   #[serde(rename = "Driver")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub driver : Option<String>,
+
+   #[serde(rename = "Platform")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub platform : Option<String>,
+
+   #[serde(rename = "MountLabel")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mount_label : Option<String>,
+
+   #[serde(rename = "ProcessLabel")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub process_label : Option<String>,
+
+   #[serde(rename = "AppArmorProfile")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub app_armor_profile : Option<String>,
+
+   #[serde(rename = "ExecIDs")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub exec_ids : Option<Vec<String>>,
+
+   // Only filled in when inspect was asked for the size
+   #[serde(rename = "SizeRw")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub size_rw : Option<i64>,
+
+   #[serde(rename = "SizeRootFs")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub size_root_fs : Option<i64>,
+
+   // The configuration the container was created with
+   #[serde(rename = "Config")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub config : Option<ContainerCreateConfig>,
+
+   #[serde(rename = "HostConfig")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub host_config : Option<HostConfig>,
+
+   #[serde(rename = "NetworkSettings")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub network_settings : Option<NetworkSettings>,
+
+   #[serde(rename = "Mounts")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub mounts : Option<Vec<MountPoint>>,
+}
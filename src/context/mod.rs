@@ -0,0 +1,326 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Contexts of the docker cli, as created by `docker context create`.
+// Each one is a directory named after the SHA-256 of the context name:
+//
+//     ~/.docker/contexts/meta/<digest>/meta.json
+//     ~/.docker/contexts/tls/<digest>/docker/{ca,cert,key}.pem
+//
+// The one in use comes from DOCKER_CONTEXT or `currentContext` in
+// `config.json`, DOCKER_HOST overrides both.
+
+mod sha256;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use auth::{config_dir, ConfigFile};
+use common::from_json;
+use config::{parse_host, Config, TlsConfig};
+use error::DockerError;
+
+// Context that stands for DOCKER_HOST or the local socket
+pub const DEFAULT_CONTEXT: &'static str = "default";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Endpoint {
+   #[serde(rename = "Host")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   host : Option<String>,
+
+   #[serde(rename = "SkipTLSVerify")]
+   #[serde(default)]
+   skip_tls_verify : bool
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Metadata {
+   #[serde(rename = "Description")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   description : Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Meta {
+   #[serde(rename = "Name")]
+   name : String,
+
+   #[serde(rename = "Metadata")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   metadata : Option<Metadata>,
+
+   #[serde(rename = "Endpoints")]
+   #[serde(default)]
+   endpoints : HashMap<String, Endpoint>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Context {
+    pub name: String,
+    pub description: Option<String>,
+
+    // Address of the daemon in DOCKER_HOST form
+    pub host: String,
+
+    pub skip_tls_verify: bool,
+
+    // Holds ca.pem, cert.pem and key.pem when the context has any
+    pub tls_dir: Option<PathBuf>
+}
+
+// Directory name of a context below `meta` and `tls`
+pub fn context_digest(name: &str) -> String {
+    sha256::hex_digest(name.as_bytes())
+}
+
+// `contexts` below DOCKER_CONFIG or `~/.docker`
+pub fn contexts_dir<F>(var: F) -> PathBuf
+    where F: Fn(&str) -> Option<String>
+{
+    config_dir(var).join("contexts")
+}
+
+// Name of the context in use, `None` for the default one
+pub fn current_context<F>(var: F) -> Result<Option<String>, DockerError>
+    where F: Fn(&str) -> Option<String>
+{
+    let var = |name: &str| var(name).and_then(|value| {
+        if value.is_empty() { None } else { Some(value) }
+    });
+
+    if var("DOCKER_HOST").is_some() {
+        return Ok(None);
+    }
+    let name = match var("DOCKER_CONTEXT") {
+        Some(name) => Some(name),
+        None => ConfigFile::from_vars(var)?.current_context
+    };
+    Ok(name.and_then(|name| if name == DEFAULT_CONTEXT { None } else { Some(name) }))
+}
+
+impl Context {
+    // Looks the context up in `~/.docker/contexts`
+    pub fn load<F>(name: &str, var: F) -> Result<Context, DockerError>
+        where F: Fn(&str) -> Option<String>
+    {
+        Context::load_from(&contexts_dir(var), name)
+    }
+
+    pub fn load_from(dir: &Path, name: &str) -> Result<Context, DockerError> {
+        let digest = context_digest(name);
+        let mut text = String::new();
+        match File::open(dir.join("meta").join(digest.as_str()).join("meta.json")) {
+            Ok(mut file) => file.read_to_string(&mut text)?,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound =>
+                return Err(DockerError::Docker(format!("context \"{}\" does not exist", name))),
+            Err(error) => return Err(DockerError::from(error))
+        };
+
+        let meta : Meta = from_json(text.as_str())?;
+        let endpoint = meta.endpoints.get("docker")
+            .ok_or(DockerError::Docker(format!("context \"{}\" has no docker endpoint", name)))?;
+        let host = endpoint.host.clone()
+            .ok_or(DockerError::Docker(format!("context \"{}\" has no docker host", name)))?;
+
+        let tls_dir = dir.join("tls").join(digest.as_str()).join("docker");
+        Ok(Context {
+            name: meta.name,
+            description: meta.metadata.and_then(|metadata| metadata.description),
+            host: host,
+            skip_tls_verify: endpoint.skip_tls_verify,
+            tls_dir: if tls_dir.exists() { Some(tls_dir) } else { None }
+        })
+    }
+
+    // Settings to reach the context's daemon, TLS is used when the
+    // context has certificates or skips their verification
+    pub fn config(&self) -> Result<Config, DockerError> {
+        let tls = self.tls_dir.is_some() || self.skip_tls_verify;
+        let (host, https) = parse_host(self.host.as_str(), tls)?;
+
+        let verify = !self.skip_tls_verify;
+        let tls = if !https {
+            None
+        } else {
+            Some(match self.tls_dir {
+                Some(ref dir) => TlsConfig::from_cert_path(dir, verify),
+                // only skips verification, there are no files to look for
+                None => TlsConfig {
+                    ca: None,
+                    cert: None,
+                    key: None,
+                    verify: verify,
+                    verify_hostname: verify,
+                    server_name: None
+                }
+            })
+        };
+
+        Ok(Config {
+            host: host,
+            tls: tls,
+            ..Config::default()
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use context::context_digest;
+
+    // A DOCKER_CONFIG directory with the given config.json and contexts
+    struct ConfigDir(PathBuf);
+
+    impl ConfigDir {
+        fn new(name: &str, config: &str) -> ConfigDir {
+            let dir = env::temp_dir().join(format!("docker-rust-context-{}-{}", name, ::std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            File::create(dir.join("config.json")).unwrap().write_all(config.as_bytes()).unwrap();
+            ConfigDir(dir)
+        }
+
+        fn add(&self, name: &str, host: &str, tls: bool) {
+            let meta = self.0.join("contexts/meta").join(context_digest(name));
+            fs::create_dir_all(&meta).unwrap();
+            File::create(meta.join("meta.json")).unwrap().write_all(json!({
+                "Name": name,
+                "Metadata": {"Description": "test context"},
+                "Endpoints": {"docker": {"Host": host, "SkipTLSVerify": false}}
+            }).to_string().as_bytes()).unwrap();
+
+            if tls {
+                let tls = self.0.join("contexts/tls").join(context_digest(name)).join("docker");
+                fs::create_dir_all(&tls).unwrap();
+                for file in &["ca.pem", "cert.pem", "key.pem"] {
+                    File::create(tls.join(file)).unwrap();
+                }
+            }
+        }
+
+        fn vars(&self, vars: &[(&str, &str)]) -> HashMap<String, String> {
+            let mut vars : HashMap<String, String> = vars.iter()
+                .map(|&(k, v)| (String::from(k), String::from(v)))
+                .collect();
+            vars.insert(String::from("DOCKER_CONFIG"), String::from(self.0.to_str().unwrap()));
+            vars
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for ConfigDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn digest() {
+        use context::sha256::hex_digest;
+        assert!(hex_digest(b"") == "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert!(hex_digest(b"abc") == "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert!(hex_digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq") ==
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+        assert!(context_digest(&"a".repeat(1000)) ==
+                "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+    }
+
+    #[test]
+    fn current_context() {
+        use context::current_context;
+        let dir = ConfigDir::new("current", r#"{"currentContext": "remote"}"#);
+
+        let vars = dir.vars(&[]);
+        assert!(current_context(|name| vars.get(name).cloned()).unwrap() == Some(String::from("remote")));
+        let vars = dir.vars(&[("DOCKER_CONTEXT", "other")]);
+        assert!(current_context(|name| vars.get(name).cloned()).unwrap() == Some(String::from("other")));
+        let vars = dir.vars(&[("DOCKER_CONTEXT", "default")]);
+        assert!(current_context(|name| vars.get(name).cloned()).unwrap().is_none());
+        let vars = dir.vars(&[("DOCKER_HOST", "tcp://10.0.0.1")]);
+        assert!(current_context(|name| vars.get(name).cloned()).unwrap().is_none());
+    }
+
+    #[test]
+    fn tls_context() {
+        use config::{Config, Host};
+        let dir = ConfigDir::new("tls", r#"{"currentContext": "remote"}"#);
+        dir.add("remote", "tcp://10.0.0.5:2376", true);
+        dir.add("plain", "tcp://10.0.0.6", false);
+
+        let vars = dir.vars(&[("DOCKER_API_VERSION", "1.25")]);
+        let config = Config::from_vars(|name| vars.get(name).cloned()).unwrap();
+        assert!(config.host == Host::Tcp(String::from("10.0.0.5:2376")));
+        assert!(config.api_version == "1.25");
+        let tls = config.tls.unwrap();
+        assert!(tls.verify);
+        assert!(tls.ca.unwrap().starts_with(dir.path().join("contexts/tls")));
+
+        let vars = dir.vars(&[("DOCKER_CONTEXT", "plain")]);
+        let config = Config::from_vars(|name| vars.get(name).cloned()).unwrap();
+        assert!(config.host == Host::Tcp(String::from("10.0.0.6:2375")));
+        assert!(config.tls.is_none());
+
+        let vars = dir.vars(&[("DOCKER_CONTEXT", "missing")]);
+        assert!(Config::from_vars(|name| vars.get(name).cloned()).is_err());
+    }
+
+    #[test]
+    fn client_from_context() {
+        use config::Config;
+        use context::Context;
+        use mock::MockDaemon;
+        use Client;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1"}));
+        let dir = ConfigDir::new("client", "{}");
+        dir.add("mock", format!("unix://{}", daemon.socket_path().display()).as_str(), false);
+
+        let vars = dir.vars(&[]);
+        let context = Context::load("mock", |name| vars.get(name).cloned()).unwrap();
+        assert!(context.description == Some(String::from("test context")));
+        let client = Client::with_config(context.config().unwrap());
+        assert!(client.images().all().unwrap().len() == 1);
+
+        let config = Config::from_context_vars("default", |name| vars.get(name).cloned()).unwrap();
+        assert!(config == Config::default());
+    }
+
+    #[test]
+    fn skip_verify_without_certificates() {
+        use context::Context;
+        let context = Context {
+            name: String::from("insecure"),
+            description: None,
+            host: String::from("tcp://10.0.0.7:2376"),
+            skip_tls_verify: true,
+            tls_dir: None
+        };
+
+        let tls = context.config().unwrap().tls.unwrap();
+        assert!(tls.ca.is_none() && tls.cert.is_none() && tls.key.is_none());
+        assert!(!tls.verify && !tls.verify_hostname);
+    }
+}
@@ -12,11 +12,195 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+use curl;
 use serde_json;
 
 #[derive(Debug)]
 pub enum DockerError {
+    // Generic error raised by the client itself
     Docker(String),
-    Json(serde_json::error::Error)
+
+    // The request never reached the daemon or the connection broke
+    Transport(curl::Error),
+
+    // Reading or writing a stream failed
+    Io(io::Error),
+
+    // 304, the object is already in the requested state
+    NotModified { message: String },
+
+    // 400, bad parameter
+    BadParameter { message: String },
+
+    // 401 or 403, the daemon refused the credentials
+    Unauthorized { message: String },
+
+    // 404, no such object
+    NotFound { message: String },
+
+    // 409, conflict with the current state of the object
+    Conflict { message: String },
+
+    // 5xx, the daemon failed to handle the request
+    ServerError { status: u32, message: String },
+
+    // Any other error status. For all status variants the message is
+    // taken from the `{"message": ...}` body if there is one
+    Http { status: u32, message: String },
+
+    // The response body could not be decoded into the expected type
+    Json { error: serde_json::Error, payload: String },
+
+    // The response body is not valid UTF-8
+    Utf8(FromUtf8Error),
+
+    // The endpoint or field needs a newer API than the one in use
+    UnsupportedApiVersion { required: String, current: String }
+}
+
+#[derive(Deserialize)]
+struct ErrorMessage {
+    message: String
+}
+
+impl DockerError {
+    pub fn from_response(status: u32, body: &[u8]) -> DockerError {
+        let message = error_message(body);
+        match status {
+            304 => DockerError::NotModified { message: message },
+            400 => DockerError::BadParameter { message: message },
+            401 | 403 => DockerError::Unauthorized { message: message },
+            404 => DockerError::NotFound { message: message },
+            409 => DockerError::Conflict { message: message },
+            _ if status >= 500 => DockerError::ServerError { status: status, message: message },
+            _ => DockerError::Http { status: status, message: message }
+        }
+    }
+
+    // The HTTP status the daemon answered with, if the error came from one
+    pub fn status(&self) -> Option<u32> {
+        match *self {
+            DockerError::NotModified { .. } => Some(304),
+            DockerError::BadParameter { .. } => Some(400),
+            DockerError::Unauthorized { .. } => Some(401),
+            DockerError::NotFound { .. } => Some(404),
+            DockerError::Conflict { .. } => Some(409),
+            DockerError::ServerError { status, .. } => Some(status),
+            DockerError::Http { status, .. } => Some(status),
+            _ => None
+        }
+    }
+}
+
+// Extracts the daemon message out of an error body, falls back to
+// the raw body when it is not the usual json object
+pub fn error_message(body: &[u8]) -> String {
+    match serde_json::from_slice::<ErrorMessage>(body) {
+        Ok(error) => error.message,
+        Err(_) => String::from_utf8_lossy(body).trim().to_string()
+    }
+}
+
+impl fmt::Display for DockerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DockerError::Docker(ref message) => write!(f, "{}", message),
+            DockerError::Transport(ref error) => write!(f, "transport error: {}", error),
+            DockerError::Io(ref error) => write!(f, "i/o error: {}", error),
+            DockerError::NotModified { ref message } => write!(f, "not modified: {}", message),
+            DockerError::BadParameter { ref message } => write!(f, "bad parameter: {}", message),
+            DockerError::Unauthorized { ref message } => write!(f, "unauthorized: {}", message),
+            DockerError::NotFound { ref message } => write!(f, "not found: {}", message),
+            DockerError::Conflict { ref message } => write!(f, "conflict: {}", message),
+            DockerError::ServerError { status, ref message } |
+            DockerError::Http { status, ref message } =>
+                write!(f, "daemon responded with status {}: {}", status, message),
+            DockerError::Json { ref error, ref payload } =>
+                write!(f, "could not decode response ({}): {}", error, payload),
+            DockerError::Utf8(ref error) => write!(f, "response is not valid utf-8: {}", error),
+            DockerError::UnsupportedApiVersion { ref required, ref current } =>
+                write!(f, "requires API version {}, client is using {}", required, current)
+        }
+    }
+}
+
+impl Error for DockerError {
+    fn description(&self) -> &str {
+        match *self {
+            DockerError::Docker(ref message) => message.as_str(),
+            DockerError::Transport(_) => "transport error",
+            DockerError::Io(_) => "i/o error",
+            DockerError::NotModified { .. } => "not modified",
+            DockerError::BadParameter { .. } => "bad parameter",
+            DockerError::Unauthorized { .. } => "unauthorized",
+            DockerError::NotFound { .. } => "not found",
+            DockerError::Conflict { .. } => "conflict",
+            DockerError::ServerError { .. } => "daemon server error",
+            DockerError::Http { .. } => "daemon responded with an error status",
+            DockerError::Json { .. } => "could not decode response",
+            DockerError::Utf8(_) => "response is not valid utf-8",
+            DockerError::UnsupportedApiVersion { .. } => "unsupported API version"
+        }
+    }
+}
+
+impl From<curl::Error> for DockerError {
+    fn from(error: curl::Error) -> DockerError {
+        DockerError::Transport(error)
+    }
 }
 
+impl From<io::Error> for DockerError {
+    fn from(error: io::Error) -> DockerError {
+        DockerError::Io(error)
+    }
+}
+
+impl From<FromUtf8Error> for DockerError {
+    fn from(error: FromUtf8Error) -> DockerError {
+        DockerError::Utf8(error)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn message_from_json_body() {
+        use error::DockerError;
+        let error = DockerError::from_response(404, b"{\"message\":\"No such container: foo\"}");
+        match error {
+            DockerError::NotFound { message } => assert!(message == "No such container: foo"),
+            _ => panic!("expected not found")
+        }
+    }
+
+    #[test]
+    fn status_mapping() {
+        use error::DockerError;
+        assert!(DockerError::from_response(304, b"").status() == Some(304));
+        match DockerError::from_response(409, b"{\"message\":\"in use\"}") {
+            DockerError::Conflict { .. } => (),
+            _ => panic!("expected conflict")
+        }
+        match DockerError::from_response(503, b"") {
+            DockerError::ServerError { status, .. } => assert!(status == 503),
+            _ => panic!("expected server error")
+        }
+        match DockerError::from_response(418, b"") {
+            DockerError::Http { status, .. } => assert!(status == 418),
+            _ => panic!("expected generic http error")
+        }
+    }
+
+    #[test]
+    fn message_from_plain_body() {
+        use error::error_message;
+        assert!(error_message(b"page not found\n") == "page not found");
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Minimal HTTP/1.1 framing on top of any byte stream, for the places
+// curl can't reach: the mock daemon, hijacked connections and
+// tunnels through child processes.
+
+use std::io::{self, BufRead, Read, Write};
+
+// Largest body `read_body` keeps in memory. Sizes come from the peer,
+// anything bigger has to go through a `BodyReader`.
+const MAX_BODY: usize = 256 * 1024 * 1024;
+
+// Request or status line plus headers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Head {
+    pub start: String,
+    pub headers: Vec<(String, String)>
+}
+
+impl Head {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|&&(ref key, _)| key.eq_ignore_ascii_case(name))
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        self.header("Transfer-Encoding").map_or(false, |value| value.eq_ignore_ascii_case("chunked"))
+    }
+
+    pub fn content_length(&self) -> Option<usize> {
+        self.header("Content-Length").and_then(|value| value.trim().parse().ok())
+    }
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    while line.ends_with('\n') || line.ends_with('\r') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+// Reads the head of a message, `None` when the peer closed the
+// connection before sending anything
+pub fn read_head<R: BufRead>(reader: &mut R) -> io::Result<Option<Head>> {
+    let start = loop {
+        match read_line(reader)? {
+            None => return Ok(None),
+            // tolerate stray line breaks between pipelined messages
+            Some(ref line) if line.is_empty() => continue,
+            Some(line) => break line
+        }
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        match read_line(reader)? {
+            None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "incomplete http head")),
+            Some(ref line) if line.is_empty() => break,
+            Some(line) => {
+                let mut parts = line.splitn(2, ':');
+                if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                    headers.push((String::from(name.trim()), String::from(value.trim())));
+                }
+            }
+        }
+    }
+
+    Ok(Some(Head { start: start, headers: headers }))
+}
+
+// Appends `size` more bytes to `body`, growing it only as fast as they
+// arrive
+fn read_more<R: BufRead>(reader: &mut R, body: &mut Vec<u8>, size: usize) -> io::Result<()> {
+    if size > MAX_BODY - body.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "body too large"));
+    }
+    if reader.by_ref().take(size as u64).read_to_end(body)? < size {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "body ended early"));
+    }
+    Ok(())
+}
+
+// Reads a body framed by Content-Length or chunked encoding. Without
+// either the body is empty, unless `until_eof` is set as it is for
+// responses of a connection that gets closed afterwards.
+pub fn read_body<R: BufRead>(reader: &mut R, head: &Head, until_eof: bool) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    if head.is_chunked() {
+        loop {
+            let line = read_line(reader)?
+                .ok_or(io::Error::new(io::ErrorKind::UnexpectedEof, "missing chunk size"))?;
+            let size = line.split(';').next().unwrap_or("");
+            let size = usize::from_str_radix(size.trim(), 16)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid chunk size"))?;
+            if size == 0 {
+                // trailers, up to the empty line
+                while let Some(line) = read_line(reader)? {
+                    if line.is_empty() {
+                        break;
+                    }
+                }
+                return Ok(body);
+            }
+
+            read_more(reader, &mut body, size)?;
+            read_line(reader)?;
+        }
+    }
+
+    match head.content_length() {
+        Some(length) => read_more(reader, &mut body, length)?,
+        None if until_eof => {
+            reader.by_ref().take(MAX_BODY as u64 + 1).read_to_end(&mut body)?;
+            if body.len() > MAX_BODY {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "body too large"));
+            }
+        }
+        None => ()
+    }
+    Ok(body)
+}
+
+enum Framing {
+    Length(usize),
+
+    // bytes left of the current chunk, `None` before its size is read
+    Chunked(Option<usize>),
+
+    Eof,
+    Done
+}
+
+// Hands out a body while it arrives, with the same framing rules as
+// `read_body`
+pub struct BodyReader<R> {
+    reader: R,
+    framing: Framing
+}
+
+impl<R: BufRead> BodyReader<R> {
+    pub fn new(reader: R, head: &Head, until_eof: bool) -> BodyReader<R> {
+        let framing = if head.is_chunked() {
+            Framing::Chunked(None)
+        } else {
+            match head.content_length() {
+                Some(length) => Framing::Length(length),
+                None if until_eof => Framing::Eof,
+                None => Framing::Done
+            }
+        };
+
+        BodyReader {
+            reader: reader,
+            framing: framing
+        }
+    }
+
+    fn read_chunk_size(&mut self) -> io::Result<usize> {
+        let line = read_line(&mut self.reader)?
+            .ok_or(io::Error::new(io::ErrorKind::UnexpectedEof, "missing chunk size"))?;
+        let size = line.split(';').next().unwrap_or("");
+        usize::from_str_radix(size.trim(), 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid chunk size"))
+    }
+}
+
+impl<R: BufRead> Read for BodyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            match self.framing {
+                Framing::Done => return Ok(0),
+                Framing::Eof => return self.reader.read(buf),
+                Framing::Length(0) => self.framing = Framing::Done,
+                Framing::Length(left) => {
+                    let limit = if buf.len() < left { buf.len() } else { left };
+                    let count = self.reader.read(&mut buf[..limit])?;
+                    if count == 0 {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "body shorter than its length"));
+                    }
+                    self.framing = Framing::Length(left - count);
+                    return Ok(count);
+                }
+                Framing::Chunked(None) => {
+                    let size = self.read_chunk_size()?;
+                    if size == 0 {
+                        while let Some(line) = read_line(&mut self.reader)? {
+                            if line.is_empty() {
+                                break;
+                            }
+                        }
+                        self.framing = Framing::Done;
+                    } else {
+                        self.framing = Framing::Chunked(Some(size));
+                    }
+                }
+                Framing::Chunked(Some(left)) => {
+                    let limit = if buf.len() < left { buf.len() } else { left };
+                    let count = self.reader.read(&mut buf[..limit])?;
+                    if count == 0 {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "incomplete chunk"));
+                    }
+                    if count == left {
+                        // line break after the chunk data
+                        read_line(&mut self.reader)?;
+                        self.framing = Framing::Chunked(None);
+                    } else {
+                        self.framing = Framing::Chunked(Some(left - count));
+                    }
+                    return Ok(count);
+                }
+            }
+        }
+    }
+}
+
+// Only the mock daemon answers requests
+#[cfg(any(test, feature = "mock"))]
+pub fn reason(status: u32) -> &'static str {
+    match status {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown"
+    }
+}
+
+pub fn write_head<W: Write>(writer: &mut W, start: &str, headers: &[(String, String)]) -> io::Result<()> {
+    let mut head = format!("{}\r\n", start);
+    for &(ref name, ref value) in headers.iter() {
+        head.push_str(format!("{}: {}\r\n", name, value).as_str());
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())
+}
+
+// Writes a complete message with a Content-Length header
+pub fn write_message<W: Write>(writer: &mut W, start: &str, headers: &[(String, String)], body: &[u8]) -> io::Result<()> {
+    let mut headers = headers.to_vec();
+    headers.push((String::from("Content-Length"), body.len().to_string()));
+    write_head(writer, start, &headers)?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+// Writes one chunk of a `Transfer-Encoding: chunked` body, an empty
+// chunk ends the body
+#[cfg(any(test, feature = "mock"))]
+pub fn write_chunk<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    write!(writer, "{:x}\r\n", data.len())?;
+    writer.write_all(data)?;
+    writer.write_all(b"\r\n")?;
+    writer.flush()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+    use http::{read_body, read_head};
+
+    #[test]
+    fn content_length_body() {
+        let mut reader = BufReader::new(&b"POST /secrets/create HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}"[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        assert!(head.start == "POST /secrets/create HTTP/1.1");
+        assert!(head.header("content-length") == Some("2"));
+        assert!(read_body(&mut reader, &head, false).unwrap() == b"{}");
+        assert!(read_head(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn chunked_body() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;x=y\r\nde\r\n0\r\n\r\n";
+        let mut reader = BufReader::new(&raw[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        assert!(read_body(&mut reader, &head, false).unwrap() == b"abcde");
+    }
+
+    #[test]
+    fn body_sizes_from_the_peer() {
+        use std::io::ErrorKind;
+        let mut reader = BufReader::new(&b"HTTP/1.1 200 OK\r\nContent-Length: 1000000000000\r\n\r\n{}"[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        assert!(read_body(&mut reader, &head, false).unwrap_err().kind() == ErrorKind::InvalidData);
+
+        let mut reader = BufReader::new(&b"HTTP/1.1 200 OK\r\nContent-Length: 100000000\r\n\r\n{}"[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        assert!(read_body(&mut reader, &head, false).unwrap_err().kind() == ErrorKind::UnexpectedEof);
+
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\nfffffffffff\r\nde";
+        let mut reader = BufReader::new(&raw[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        assert!(read_body(&mut reader, &head, false).unwrap_err().kind() == ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn body_reader() {
+        use std::io::{BufRead, BufReader, Read};
+        use http::BodyReader;
+        let mut reader = BufReader::new(&b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                                           5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\nrest"[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        {
+            let mut body = BodyReader::new(&mut reader, &head, false);
+            let mut first = [0u8; 3];
+            body.read_exact(&mut first).unwrap();
+            assert!(&first == b"hel");
+            let mut text = String::new();
+            body.read_to_string(&mut text).unwrap();
+            assert!(text == "lo, world");
+        }
+        assert!(reader.fill_buf().unwrap() == b"rest");
+
+        let mut reader = BufReader::new(&b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nabcdef"[..]);
+        let head = read_head(&mut reader).unwrap().unwrap();
+        let mut text = String::new();
+        BodyReader::new(&mut reader, &head, true).read_to_string(&mut text).unwrap();
+        assert!(text == "abc");
+    }
+}
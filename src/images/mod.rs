@@ -34,9 +34,12 @@ impl<'a> ImagesClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use images::ImagesClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_image(&json!({"Id": "sha256:1", "RepoTags": ["alpine:latest"]}));
+        daemon.add_image(&json!({"Id": "sha256:2", "RepoTags": ["debian:latest"]}));
+        let client = daemon.client();
         let image_client = ImagesClient::new(&client);
         let images = image_client.all();
         assert!(images.is_ok());
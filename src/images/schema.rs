@@ -26,17 +26,22 @@ pub struct HealthConfig {
    // The time to wait between checks in nanoseconds. 0 means inherit.
    #[serde(rename = "Interval")]
    #[serde(skip_serializing_if = "Option::is_none")]
-   pub interval : Option<i32>,
+   pub interval : Option<i64>,
 
    // The time to wait before considering the check to have hung. 0 means inherit.
    #[serde(rename = "Timeout")]
    #[serde(skip_serializing_if = "Option::is_none")]
-   pub timeout : Option<i32>,
+   pub timeout : Option<i64>,
 
    // The number of consecutive failures needed to consider a container as unhealthy. 0 means inherit.
    #[serde(rename = "Retries")]
    #[serde(skip_serializing_if = "Option::is_none")]
    pub retries : Option<i32>,
+
+   // The time to wait before counting failures in nanoseconds, API 1.29 and newer.
+   #[serde(rename = "StartPeriod")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub start_period : Option<i64>,
 }
 
 // An object mapping mount point paths inside the container to empty objects.
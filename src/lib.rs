@@ -18,12 +18,30 @@
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg_attr(any(test, feature = "mock"), macro_use)]
 extern crate serde_json;
 extern crate curl;
+extern crate curl_sys;
+extern crate libc;
+extern crate base64;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "async")]
+extern crate futures_cpupool;
 
 #[macro_use]
 mod common;
-mod error;
+pub mod error;
+pub mod config;
+pub mod auth;
+pub mod context;
+pub mod builder;
+pub mod transport;
+mod http;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(feature = "async")]
+pub mod async_client;
 
 pub mod images;
 pub mod networks;
@@ -32,15 +50,20 @@ pub mod swarm;
 pub mod volumes;
 pub mod tasks;
 pub mod secrets;
+pub mod system;
 
-use std::str::*;
-use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::io::Read;
+use std::sync::Arc;
 
-use curl::easy::Easy;
-use std::error::Error;
-
-use error::DockerError;
+pub use error::DockerError;
+pub use config::{Config, Host, TlsConfig, MAX_API_VERSION};
+pub use auth::RegistryAuth;
+pub use builder::ClientBuilder;
+pub use common::{JsonStream, LogLine, LogLines, LogOutput, LogStream};
+pub use transport::{HijackedStream, Request, Response, StreamingResponse, Transport};
+#[cfg(feature = "async")]
+pub use async_client::AsyncClient;
 use images::ImagesClient;
 use containers::ContainersClient;
 use networks::NetworksClient;
@@ -48,95 +71,169 @@ use swarm::SwarmClient;
 use volumes::VolumesClient;
 use tasks::TasksClient;
 use secrets::SecretsClient;
+use system::SystemClient;
+use system::schema::Version;
+use config::compare_versions;
+use transport::CurlTransport;
 
 
 pub struct Client {
-    api_url: String,
-    curl : RefCell<Easy>
+    // Prepended to every endpoint path, carries the API version
+    prefix: String,
+    config: Config,
+    transport: Arc<Transport>
 }
 
 impl Client {
 
+    // Talks to the local socket, every path is appended to `api_url`
     pub fn new(api_url: &str) -> Client {
-        let client = Client {
-            api_url : String::from_str(api_url).unwrap(),
-            curl : RefCell::new(Easy::new())
-        };
-        client.set_curl_client();
+        let config = Config::default();
+        Client {
+            prefix : String::from("/"),
+            transport : Arc::new(CurlTransport::with_base_url(&config, api_url)),
+            config : config
+        }
+    }
 
-        client
+    pub fn with_config(config: Config) -> Client {
+        let transport = transport::for_config(&config);
+        Client::with_transport(config, transport)
     }
 
-    pub fn from_env() -> Client {
-        Client::new("http://v1.27/")
+    // Sends every request through `transport` instead of curl
+    pub fn with_transport(config: Config, transport: Arc<Transport>) -> Client {
+        Client {
+            prefix : format!("/v{}/", config.api_version),
+            config : config,
+            transport : transport
+        }
     }
 
-    fn set_curl_client(&self) -> ()  {
-        let mut curl = self.curl.borrow_mut();
-        let _ = curl.unix_socket("/var/run/docker.sock");
+    pub fn from_env() -> Result<Client, DockerError> {
+        Ok(Client::with_config(Config::from_env()?))
     }
 
-    fn get(&self, url: &str) -> Result<String, DockerError> {
-        let mut result = Vec::new();
-        let real_url = format!("{}{}", self.api_url, url);
-        let mut curl = self.curl.borrow_mut();
-        curl.get(true).unwrap();        
-        curl.url(real_url.as_str()).unwrap();
+    // Talks to the daemon of a docker cli context, e.g. one created
+    // with `docker context create`
+    pub fn from_context(name: &str) -> Result<Client, DockerError> {
+        Ok(Client::with_config(Config::from_context(name)?))
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
 
-        {
-            let mut transfer = curl.transfer();
-            transfer.write_function(|data| {
-                result.extend_from_slice(data);
-                Ok(data.len())
-            }).unwrap();
-            transfer.perform().unwrap()
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // Sends `request` to the versioned API, its path is relative to
+    // the API root, e.g. `containers/json`. Error statuses come back
+    // as `DockerError`s.
+    pub fn execute(&self, request: Request) -> Result<Response, DockerError> {
+        let response = self.send(self.versioned(request))?;
+        if response.status == 304 || response.status >= 400 {
+            return Err(DockerError::from_response(response.status, &response.body));
+        }
+
+        Ok(response)
+    }
+
+    // Like `execute` but the body is read while it arrives, for
+    // long-lived or large responses
+    pub fn execute_stream(&self, request: Request) -> Result<StreamingResponse, DockerError> {
+        let request = self.with_headers(self.versioned(request));
+        let mut response = self.transport.stream(&request)?;
+        if response.status == 304 || response.status >= 400 {
+            let mut body = Vec::new();
+            response.read_to_end(&mut body)?;
+            return Err(DockerError::from_response(response.status, &body));
         }
 
-        Ok(String::from_utf8(result).unwrap())
-    }   
-
-    fn post(&self, url: &str, mut payload: &[u8]) -> Result<String, DockerError> {
-        let mut result = Vec::new();
-        let mut curl = self.curl.borrow_mut();
-        let real_url = format!("{}{}", self.api_url, url);
-        curl.url(real_url.as_str()).unwrap();
-        curl.post(true).unwrap();        
-        curl.post_field_size(payload.len() as u64).unwrap();
-
-        {
-            let mut send_transfer = curl.transfer();
-            send_transfer.read_function(|buf| {
-                    Ok(payload.read(buf).unwrap_or(0))
-            }).unwrap();
-            send_transfer.write_function(|data| {
-                result.extend_from_slice(data);
-                Ok(data.len())
-            }).unwrap();
-            send_transfer.perform().unwrap();
+        Ok(response)
+    }
+
+    // Asks the daemon to turn the connection into a raw stream, for
+    // attach and exec start
+    pub fn hijack(&self, request: Request) -> Result<HijackedStream, DockerError> {
+        let request = self.with_headers(self.versioned(request));
+        self.transport.hijack(&request)
+    }
+
+    // Sends `request` as is, with an absolute path and whatever
+    // status the daemon answers with
+    pub fn send(&self, request: Request) -> Result<Response, DockerError> {
+        self.transport.request(&self.with_headers(request))
+    }
+
+    fn versioned(&self, mut request: Request) -> Request {
+        request.path = {
+            let path = if request.path.starts_with('/') { &request.path[1..] } else { &request.path[..] };
+            format!("{}{}", self.prefix, path)
+        };
+        request
+    }
+
+    fn with_headers(&self, mut request: Request) -> Request {
+        let mut headers = vec![(String::from("User-Agent"), self.config.user_agent.clone())];
+        headers.extend(self.config.headers.iter().cloned());
+        headers.extend(request.headers.drain(..));
+        request.headers = headers;
+        request
+    }
+
+    // Version of the API the requests are made against
+    pub fn api_version(&self) -> &str {
+        self.config.api_version.as_str()
+    }
+
+    // Fails with `UnsupportedApiVersion` when the negotiated version
+    // is older than `required`
+    pub fn require_api_version(&self, required: &str) -> Result<(), DockerError> {
+        if compare_versions(self.api_version(), required) == Ordering::Less {
+            return Err(DockerError::UnsupportedApiVersion {
+                required: String::from(required),
+                current: String::from(self.api_version())
+            });
         }
 
-        Ok(String::from_utf8(result).unwrap())
+        Ok(())
     }
 
+    // Asks the daemon which API version it speaks and switches to the
+    // lower of that and `MAX_API_VERSION`
+    pub fn negotiate_version(&mut self) -> Result<(), DockerError> {
+        let daemon_version = self.daemon_api_version()?;
+        let version = if compare_versions(daemon_version.as_str(), MAX_API_VERSION) == Ordering::Less {
+            daemon_version
+        } else {
+            String::from(MAX_API_VERSION)
+        };
 
-    fn delete(&self, url: &str) -> Result<String, DockerError> {
-        let mut result = Vec::new();
-        let real_url = format!("{}{}", self.api_url, url);
-        let mut curl = self.curl.borrow_mut();
-        curl.custom_request("DELETE").unwrap();        
-        curl.url(real_url.as_str()).unwrap();
+        self.prefix = format!("/v{}/", version);
+        self.config.api_version = version;
+        Ok(())
+    }
 
-        {
-            let mut transfer = curl.transfer();
-            transfer.write_function(|data| {
-                result.extend_from_slice(data);
-                Ok(data.len())
-            }).unwrap();
-            transfer.perform().unwrap()
+    fn daemon_api_version(&self) -> Result<String, DockerError> {
+        // both endpoints are also served without a version prefix,
+        // which keeps them working whatever version we start with
+        let ping = self.send(Request::new("GET", "/_ping"))?;
+        if ping.status >= 400 {
+            return Err(DockerError::from_response(ping.status, &ping.body));
+        }
+        if let Some(version) = ping.header("API-Version") {
+            return Ok(String::from(version));
         }
 
-        Ok(String::from_utf8(result).unwrap())
-    }   
+        let response = self.send(Request::new("GET", "/version"))?;
+        if response.status >= 400 {
+            return Err(DockerError::from_response(response.status, &response.body));
+        }
+        let version : Version = common::from_json(response.text()?.as_str())?;
+        Ok(version.api_version)
+    }
 
     pub fn images(&self) -> ImagesClient {
         ImagesClient::new(self)
@@ -164,4 +261,8 @@ impl Client {
     pub fn secrets(&self) -> SecretsClient {
         SecretsClient::new(self)
     }
+
+    pub fn system(&self) -> SystemClient {
+        SystemClient::new(self)
+    }
 }
@@ -0,0 +1,921 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Fake Docker Engine API server for tests that should not depend on
+// a real daemon. It listens on a temporary unix socket and keeps its
+// state in memory, tests script it through `MockDaemon`:
+//
+//     let daemon = MockDaemon::start();
+//     daemon.add_container(&json!({"Id": "abc", "Names": ["/web"]}));
+//     let client = daemon.client();
+//     assert!(client.containers().all().unwrap().len() == 1);
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use base64;
+use serde::Serialize;
+use serde_json::{self, Value};
+
+use super::Client;
+use builder::ClientBuilder;
+use config::{compare_versions, MAX_API_VERSION};
+use http;
+use transport::{parse_query, Request, Response};
+
+static SOCKETS: AtomicUsize = AtomicUsize::new(0);
+
+// A canned answer that takes precedence over the built in routes
+struct Route {
+    method: String,
+    path: String,
+    response: Response
+}
+
+pub struct MockState {
+    pub api_version: String,
+    pub containers: Vec<Value>,
+    pub images: Vec<Value>,
+    pub networks: Vec<Value>,
+    pub volumes: Vec<Value>,
+    pub secrets: Vec<Value>,
+    pub tasks: Vec<Value>,
+
+    // Sent by `/events`, which keeps the connection open afterwards
+    // unless the request has an `until`
+    pub events: Vec<Value>,
+
+    // `None` answers like a node that is not part of a swarm
+    pub swarm: Option<Value>,
+
+    // Archives put into containers, by container id and path
+    pub archives: HashMap<(String, String), Vec<u8>>,
+
+    // Output of containers by id, as stream name, timestamp and data
+    pub logs: HashMap<String, Vec<(String, String, Vec<u8>)>>,
+
+    // Exec instances created in containers
+    pub execs: Vec<Value>,
+
+    // Every request the daemon has received, in order
+    pub requests: Vec<Request>,
+
+    routes: Vec<Route>,
+    last_id: u64
+}
+
+pub struct MockDaemon {
+    path: PathBuf,
+    state: Arc<Mutex<MockState>>,
+    stopped: Arc<AtomicBool>
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("mock objects have to serialize to json")
+}
+
+impl MockState {
+    fn new() -> MockState {
+        MockState {
+            api_version: String::from(MAX_API_VERSION),
+            containers: Vec::new(),
+            images: Vec::new(),
+            networks: Vec::new(),
+            volumes: Vec::new(),
+            secrets: Vec::new(),
+            tasks: Vec::new(),
+            events: Vec::new(),
+            swarm: None,
+            archives: HashMap::new(),
+            logs: HashMap::new(),
+            execs: Vec::new(),
+            requests: Vec::new(),
+            routes: Vec::new(),
+            last_id: 0
+        }
+    }
+
+    // Ids look like the daemon's, 64 hex characters
+    pub fn next_id(&mut self) -> String {
+        self.last_id += 1;
+        format!("{:064x}", self.last_id)
+    }
+}
+
+impl MockDaemon {
+    pub fn start() -> MockDaemon {
+        let path = env::temp_dir().join(format!("docker-rust-mock-{}-{}.sock",
+                                                 ::std::process::id(),
+                                                 SOCKETS.fetch_add(1, Ordering::SeqCst)));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("could not bind mock daemon socket");
+
+        let daemon = MockDaemon {
+            path: path,
+            state: Arc::new(Mutex::new(MockState::new())),
+            stopped: Arc::new(AtomicBool::new(false))
+        };
+
+        let state = daemon.state.clone();
+        let stopped = daemon.stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = state.clone();
+                    thread::spawn(move || serve(stream, state));
+                }
+            }
+        });
+
+        daemon
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.path
+    }
+
+    // Builder pointing at the mock socket, for tests that need more
+    // than the defaults
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new().socket(self.path.to_str().expect("temp dir is not valid utf-8"))
+    }
+
+    pub fn client(&self) -> Client {
+        self.client_builder().build().expect("mock client configuration is valid")
+    }
+
+    // Direct access to the in-memory state
+    pub fn state(&self) -> MutexGuard<MockState> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn add_container<T: Serialize>(&self, container: &T) {
+        self.state().containers.push(to_value(container));
+    }
+
+    pub fn add_image<T: Serialize>(&self, image: &T) {
+        self.state().images.push(to_value(image));
+    }
+
+    pub fn add_network<T: Serialize>(&self, network: &T) {
+        self.state().networks.push(to_value(network));
+    }
+
+    pub fn add_volume<T: Serialize>(&self, volume: &T) {
+        self.state().volumes.push(to_value(volume));
+    }
+
+    pub fn add_secret<T: Serialize>(&self, secret: &T) {
+        self.state().secrets.push(to_value(secret));
+    }
+
+    pub fn add_task<T: Serialize>(&self, task: &T) {
+        self.state().tasks.push(to_value(task));
+    }
+
+    pub fn add_event<T: Serialize>(&self, event: &T) {
+        self.state().events.push(to_value(event));
+    }
+
+    // Appends to the output of a container, `stream` is `stdout` or
+    // `stderr`. Followed logs get it right away.
+    pub fn add_log(&self, id: &str, stream: &str, data: &str) {
+        let mut state = self.state();
+        let logs = state.logs.entry(String::from(id)).or_insert_with(Vec::new);
+        let timestamp = format!("2017-01-01T00:00:{:02}.000000000Z", logs.len());
+        logs.push((String::from(stream), timestamp, Vec::from(data.as_bytes())));
+    }
+
+    pub fn set_swarm<T: Serialize>(&self, swarm: &T) {
+        self.state().swarm = Some(to_value(swarm));
+    }
+
+    pub fn set_api_version(&self, version: &str) {
+        self.state().api_version = String::from(version);
+    }
+
+    // Answer `method path` (without version prefix and query) with
+    // the given status and body instead of the built in behaviour
+    pub fn respond(&self, method: &str, path: &str, status: u32, body: &str) {
+        let mut state = self.state();
+        state.routes.retain(|route| !(route.method == method && route.path == path));
+        state.routes.push(Route {
+            method: String::from(method),
+            path: String::from(path),
+            response: Response {
+                status: status,
+                headers: vec![(String::from("Content-Type"), String::from("application/json"))],
+                body: Vec::from(body.as_bytes())
+            }
+        });
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        // wake up the accept loop so it notices it should stop
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = UnixStream::connect(&self.path);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn serve(stream: UnixStream, state: Arc<Mutex<MockState>>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return
+    };
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let head = match http::read_head(&mut reader) {
+            Ok(Some(head)) => head,
+            _ => return
+        };
+        if head.header("Expect").map_or(false, |value| value.eq_ignore_ascii_case("100-continue")) {
+            if writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").is_err() {
+                return;
+            }
+        }
+        let body = match http::read_body(&mut reader, &head, false) {
+            Ok(body) => body,
+            Err(_) => return
+        };
+
+        let mut parts = head.start.split(' ');
+        let method = parts.next().unwrap_or("");
+        let target = parts.next().unwrap_or("/");
+        let (path, query) = match target.find('?') {
+            Some(index) => (&target[..index], parse_query(&target[index + 1..])),
+            None => (target, Vec::new())
+        };
+        let request = Request {
+            method: String::from(method),
+            path: String::from(path),
+            query: query,
+            headers: head.headers.clone(),
+            body: if body.is_empty() { None } else { Some(body) }
+        };
+
+        let (response, events, logs, upgrade) = {
+            let mut state = state.lock().unwrap();
+            state.requests.push(request.clone());
+            (route(&mut state, &request), stream_events(&state, &request),
+             stream_logs(&mut state, &request), upgrades(&mut state, &request))
+        };
+
+        if upgrade {
+            let headers = vec![(String::from("Connection"), String::from("Upgrade")),
+                               (String::from("Upgrade"), String::from("tcp"))];
+            if http::write_head(&mut writer, "HTTP/1.1 101 UPGRADED", &headers).is_ok() {
+                echo(&mut reader, &mut writer);
+            }
+            return;
+        }
+
+        if let Some(events) = events {
+            let follow = !request.query.iter().any(|&(ref key, _)| key == "until");
+            if write_events(&mut writer, &events, follow).is_err() || follow {
+                // like the daemon, keep sending nothing until the
+                // client hangs up
+                let _ = reader.read_to_end(&mut Vec::new());
+                return;
+            }
+            continue;
+        }
+
+        if let Some((id, tty)) = logs {
+            if write_logs(&mut writer, &state, &request, id.as_str(), tty).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let start = format!("HTTP/1.1 {} {}", response.status, http::reason(response.status));
+        let written = if request.method == "HEAD" {
+            let mut headers = response.headers.clone();
+            headers.push((String::from("Content-Length"), response.body.len().to_string()));
+            http::write_head(&mut writer, start.as_str(), &headers)
+        } else {
+            http::write_message(&mut writer, start.as_str(), &response.headers, &response.body)
+        };
+        if written.is_err() {
+            return;
+        }
+        if head.header("Connection").map_or(false, |value| value.eq_ignore_ascii_case("close")) {
+            return;
+        }
+    }
+}
+
+// Attached containers and started execs send back whatever they get,
+// like `cat` with a tty
+fn echo(reader: &mut BufReader<UnixStream>, writer: &mut UnixStream) {
+    loop {
+        let length = match reader.fill_buf() {
+            Ok(data) if !data.is_empty() => {
+                if writer.write_all(data).is_err() {
+                    return;
+                }
+                data.len()
+            }
+            _ => break
+        };
+        reader.consume(length);
+    }
+    let _ = writer.shutdown(Shutdown::Write);
+}
+
+// Whether `request` attaches to an existing container and asked for
+// the connection to be upgraded
+fn upgrades(state: &mut MockState, request: &Request) -> bool {
+    let (_, path) = split_version(request.path.as_str());
+    let segments : Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let upgrade = request.header("Upgrade").map_or(false, |value| value.eq_ignore_ascii_case("tcp"));
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["containers", id, "attach"]) => upgrade && find(&mut state.containers, "Id", id).is_some(),
+        ("POST", ["exec", id, "start"]) if upgrade => match find(&mut state.execs, "ID", id) {
+            Some(exec) => {
+                exec["Running"] = Value::Bool(true);
+                true
+            }
+            None => false
+        },
+        _ => false
+    }
+}
+
+fn write_events<W: Write>(writer: &mut W, events: &[Value], follow: bool) -> ::std::io::Result<()> {
+    let headers = vec![(String::from("Content-Type"), String::from("application/json")),
+                       (String::from("Transfer-Encoding"), String::from("chunked"))];
+    http::write_head(writer, "HTTP/1.1 200 OK", &headers)?;
+    for event in events.iter() {
+        let mut line = serde_json::to_vec(event).unwrap();
+        line.push(b'\n');
+        http::write_chunk(writer, &line)?;
+    }
+    if !follow {
+        http::write_chunk(writer, &[])?;
+    }
+    Ok(())
+}
+
+// Events to stream when `request` is for `/events` and no scripted
+// route overrides it
+fn stream_events(state: &MockState, request: &Request) -> Option<Vec<Value>> {
+    let (_, path) = split_version(request.path.as_str());
+    let scripted = state.routes.iter().any(|route| route.method == request.method && route.path == path);
+    if request.method != "GET" || path != "/events" || scripted {
+        return None;
+    }
+    Some(state.events.clone())
+}
+
+// Id of the container and whether it has a tty when `request` is for
+// the logs of an existing container and no scripted route overrides it
+fn stream_logs(state: &mut MockState, request: &Request) -> Option<(String, bool)> {
+    let (_, path) = split_version(request.path.as_str());
+    let scripted = state.routes.iter().any(|route| route.method == request.method && route.path == path);
+    let segments : Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["containers", id, "logs"]) if !scripted => find(&mut state.containers, "Id", id).map(|container| {
+            (String::from(*id), container.pointer("/Config/Tty").and_then(|tty| tty.as_bool()).unwrap_or(false))
+        }),
+        _ => None
+    }
+}
+
+// Sends the output of a container like the daemon does. Following
+// keeps sending new output until the container is no longer running.
+fn write_logs<W: Write>(writer: &mut W, state: &Mutex<MockState>, request: &Request,
+                        id: &str, tty: bool) -> ::std::io::Result<()> {
+    let flag = |name: &str| request.query.iter().any(|&(ref key, ref value)| key == name && value == "1");
+    let tail = request.query.iter()
+        .find(|&&(ref key, _)| key == "tail")
+        .and_then(|&(_, ref value)| value.parse::<usize>().ok());
+    let write_entry = |writer: &mut W, &(ref stream, ref timestamp, ref data): &(String, String, Vec<u8>)| {
+        let kind = if stream == "stderr" { 2 } else { 1 };
+        if !flag(stream.as_str()) {
+            return Ok(());
+        }
+        let mut line = Vec::new();
+        if flag("timestamps") {
+            line.extend_from_slice(timestamp.as_bytes());
+            line.push(b' ');
+        }
+        line.extend_from_slice(data);
+        if !tty {
+            let length = line.len();
+            let header = vec![kind, 0, 0, 0, (length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8];
+            line = header.into_iter().chain(line.into_iter()).collect();
+        }
+        http::write_chunk(writer, &line)
+    };
+
+    let content_type = if tty {
+        "application/vnd.docker.raw-stream"
+    } else {
+        "application/vnd.docker.multiplexed-stream"
+    };
+    let headers = vec![(String::from("Content-Type"), String::from(content_type)),
+                       (String::from("Transfer-Encoding"), String::from("chunked"))];
+    http::write_head(writer, "HTTP/1.1 200 OK", &headers)?;
+
+    let entries = state.lock().unwrap().logs.get(id).cloned().unwrap_or(Vec::new());
+    let start = tail.map_or(0, |tail| entries.len().saturating_sub(tail));
+    for entry in entries[start..].iter() {
+        write_entry(writer, entry)?;
+    }
+
+    let mut sent = entries.len();
+    while flag("follow") {
+        thread::sleep(Duration::from_millis(10));
+        let (entries, running) = {
+            let mut state = state.lock().unwrap();
+            let entries = state.logs.get(id).map_or(Vec::new(), |logs| logs[sent..].to_vec());
+            let running = find(&mut state.containers, "Id", id)
+                .and_then(|container| container.get("State").and_then(|value| value.as_str()).map(String::from))
+                .map_or(false, |status| status == "running" || status == "paused");
+            (entries, running)
+        };
+        for entry in entries.iter() {
+            write_entry(writer, entry)?;
+        }
+        sent += entries.len();
+        if !running {
+            break;
+        }
+    }
+    http::write_chunk(writer, &[])
+}
+
+// Applies `all`, `limit` and the status, label, name and ancestor
+// filters, newest containers come first like with the daemon
+fn list_containers(containers: &[Value], request: &Request) -> Result<Vec<Value>, String> {
+    let param = |name: &str| request.query.iter()
+        .find(|&&(ref key, _)| key == name)
+        .map(|&(_, ref value)| value.clone());
+    let filters : BTreeMap<String, Vec<String>> = match param("filters") {
+        Some(filters) => serde_json::from_str(filters.as_str())
+            .map_err(|error| format!("invalid filters: {}", error))?,
+        None => BTreeMap::new()
+    };
+    let all = param("all").map_or(false, |all| all == "1" || all == "true");
+    let limit = match param("limit") {
+        Some(limit) => Some(limit.parse::<usize>().map_err(|_| String::from("invalid limit"))?),
+        None => None
+    };
+
+    let text = |container: &Value, key: &str| String::from(container.get(key).and_then(|value| value.as_str()).unwrap_or(""));
+    let matches = |container: &Value, key: &str, value: &str| match key {
+        "status" => text(container, "State") == value,
+        "ancestor" => text(container, "Image") == value,
+        "name" => container.get("Names").and_then(|names| names.as_array())
+            .map_or(false, |names| names.iter().any(|name| name.as_str().map_or(false, |name| name.contains(value)))),
+        "label" => {
+            let mut parts = value.splitn(2, '=');
+            let key = parts.next().unwrap_or("");
+            match (container.get("Labels").and_then(|labels| labels.get(key)), parts.next()) {
+                (Some(_), None) => true,
+                (Some(label), Some(expected)) => label.as_str() == Some(expected),
+                (None, _) => false
+            }
+        }
+        _ => true
+    };
+
+    let mut listed : Vec<Value> = containers.iter().rev()
+        .filter(|container| {
+            // containers added without a state count as running
+            let status = text(container, "State");
+            all || limit.is_some() || filters.contains_key("status") ||
+                status == "" || status == "running" || status == "paused"
+        })
+        .filter(|container| filters.iter().all(|(key, values)| {
+            values.iter().any(|value| matches(container, key.as_str(), value.as_str()))
+        }))
+        .cloned()
+        .collect();
+    if let Some(limit) = limit {
+        listed.truncate(limit);
+    }
+    Ok(listed)
+}
+
+// Inspect shape of a container kept in the list shape
+fn inspect(container: &Value) -> Value {
+    let status = container.get("State").and_then(|value| value.as_str()).unwrap_or("created");
+    let running = status == "running" || status == "paused";
+    json!({
+        "Id": container.get("Id"),
+        "Name": container.pointer("/Names/0"),
+        "Image": container.get("Image"),
+        "State": {
+            "Status": status,
+            "Running": running,
+            "Paused": status == "paused",
+            "Restarting": false,
+            "OOMKilled": false,
+            "Dead": false,
+            "Pid": if running { 4242 } else { 0 },
+            "ExitCode": 0,
+            "Error": "",
+            "StartedAt": "0001-01-01T00:00:00Z",
+            "FinishedAt": "0001-01-01T00:00:00Z"
+        },
+        "RestartCount": 0,
+        "Config": container.get("Config"),
+        "HostConfig": container.get("HostConfig"),
+        "NetworkSettings": { "Networks": {}, "Ports": {} },
+        "Mounts": []
+    })
+}
+
+fn json_response(status: u32, value: &Value) -> Response {
+    Response {
+        status: status,
+        headers: vec![(String::from("Content-Type"), String::from("application/json"))],
+        body: serde_json::to_vec(value).unwrap()
+    }
+}
+
+fn error_response(status: u32, message: &str) -> Response {
+    json_response(status, &json!({ "message": message }))
+}
+
+fn empty_response(status: u32) -> Response {
+    Response {
+        status: status,
+        headers: Vec::new(),
+        body: Vec::new()
+    }
+}
+
+// Splits `/v1.27/containers/json` into the version and the path
+fn split_version(path: &str) -> (Option<&str>, &str) {
+    if path.starts_with("/v") {
+        if let Some(end) = path[1..].find('/') {
+            let version = &path[2..end + 1];
+            if !version.is_empty() && version.chars().all(|c| c.is_digit(10) || c == '.') {
+                return (Some(version), &path[end + 1..]);
+            }
+        }
+    }
+    (None, path)
+}
+
+fn find<'a>(objects: &'a mut Vec<Value>, key: &str, id: &str) -> Option<&'a mut Value> {
+    objects.iter_mut().find(|object| {
+        object.get(key).and_then(|value| value.as_str()).map_or(false, |value| value == id)
+    })
+}
+
+fn route(state: &mut MockState, request: &Request) -> Response {
+    let (version, path) = split_version(request.path.as_str());
+    let method = request.method.as_str();
+
+    if let Some(route) = state.routes.iter().find(|route| route.method == method && route.path == path) {
+        return route.response.clone();
+    }
+
+    if let Some(version) = version {
+        if compare_versions(version, state.api_version.as_str()) == ::std::cmp::Ordering::Greater {
+            return error_response(400, format!("client version {} is too new. Maximum supported API version is {}",
+                                               version, state.api_version).as_str());
+        }
+    }
+
+    let segments : Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["_ping"]) | ("HEAD", ["_ping"]) => Response {
+            status: 200,
+            headers: vec![(String::from("API-Version"), state.api_version.clone()),
+                          (String::from("Content-Type"), String::from("text/plain"))],
+            body: Vec::from(&b"OK"[..])
+        },
+        ("GET", ["version"]) => json_response(200, &json!({
+            "Version": "17.05.0-mock",
+            "ApiVersion": state.api_version,
+            "MinAPIVersion": "1.12",
+            "Os": "linux",
+            "Arch": "amd64"
+        })),
+        ("GET", ["containers", "json"]) => match list_containers(&state.containers, request) {
+            Ok(containers) => json_response(200, &Value::Array(containers)),
+            Err(message) => error_response(400, message.as_str())
+        },
+        ("HEAD", ["containers", id, "archive"]) | ("GET", ["containers", id, "archive"]) |
+        ("PUT", ["containers", id, "archive"]) => {
+            if find(&mut state.containers, "Id", id).is_none() {
+                return error_response(404, format!("No such container: {}", id).as_str());
+            }
+            let path = request.query.iter()
+                .find(|&&(ref key, _)| key == "path")
+                .map(|&(_, ref value)| value.clone())
+                .unwrap_or(String::from("/"));
+            let key = (String::from(*id), path.clone());
+
+            if method == "PUT" {
+                state.archives.insert(key, request.body.clone().unwrap_or(Vec::new()));
+                return empty_response(200);
+            }
+
+            let archive = match state.archives.get(&key) {
+                Some(archive) => archive.clone(),
+                None => return error_response(404, format!("Could not find the file {} in container {}", path, id).as_str())
+            };
+            let stat = json!({
+                "name": path.rsplit('/').next().unwrap_or(""),
+                "size": archive.len(),
+                "mode": 2147484141u32,
+                "mtime": "2017-01-01T00:00:00Z",
+                "linkTarget": ""
+            });
+            Response {
+                status: 200,
+                headers: vec![(String::from("Content-Type"), String::from("application/x-tar")),
+                              (String::from("X-Docker-Container-Path-Stat"),
+                               base64::encode(&serde_json::to_vec(&stat).unwrap()))],
+                body: archive
+            }
+        }
+        ("GET", ["containers", id, "logs"]) => match find(&mut state.containers, "Id", id) {
+            Some(_) => empty_response(200),
+            None => error_response(404, format!("No such container: {}", id).as_str())
+        },
+        ("GET", ["containers", id, "json"]) => match find(&mut state.containers, "Id", id) {
+            Some(container) => json_response(200, &inspect(container)),
+            None => error_response(404, format!("No such container: {}", id).as_str())
+        },
+        ("POST", ["containers", "create"]) => {
+            let mut config : Value = match request.body.as_ref().map(|body| serde_json::from_slice(body)) {
+                Some(Ok(config)) => config,
+                _ => return error_response(400, "invalid JSON")
+            };
+            let image = match config.get("Image").and_then(|image| image.as_str()) {
+                Some(image) => String::from(image),
+                None => return error_response(400, "Config cannot be empty in order to create a container")
+            };
+            let known = state.images.iter().any(|known| {
+                known.get("Id").and_then(|id| id.as_str()) == Some(image.as_str()) ||
+                known.get("RepoTags").and_then(|tags| tags.as_array())
+                    .map_or(false, |tags| tags.iter().any(|tag| tag.as_str() == Some(image.as_str())))
+            });
+            if !known {
+                return error_response(404, format!("No such image: {}", image).as_str());
+            }
+
+            let id = state.next_id();
+            let name = request.query.iter()
+                .find(|&&(ref key, _)| key == "name")
+                .map(|&(_, ref value)| format!("/{}", value))
+                .unwrap_or(format!("/container_{}", &id[56..]));
+            let taken = state.containers.iter().any(|container| {
+                container.get("Names").and_then(|names| names.as_array())
+                    .map_or(false, |names| names.iter().any(|known| known.as_str() == Some(name.as_str())))
+            });
+            if taken {
+                return error_response(409, format!("Conflict. The container name \"{}\" is already in use.", name).as_str());
+            }
+
+            let host_config = config.as_object_mut()
+                .and_then(|config| config.remove("HostConfig"))
+                .unwrap_or(json!({}));
+            state.containers.push(json!({
+                "Id": id,
+                "Names": [name],
+                "Image": image,
+                "State": "created",
+                "Status": "Created",
+                "Config": config,
+                "HostConfig": host_config
+            }));
+            json_response(201, &json!({ "Id": id, "Warnings": [] }))
+        }
+        ("POST", ["containers", id, action]) if *action != "attach" && *action != "rename" && *action != "exec" => {
+            let container = match find(&mut state.containers, "Id", id) {
+                Some(container) => container,
+                None => return error_response(404, format!("No such container: {}", id).as_str())
+            };
+            let current = String::from(container.get("State").and_then(|value| value.as_str()).unwrap_or("created"));
+            let next = match (*action, current.as_str()) {
+                ("start", "running") | ("start", "paused") | ("stop", "created") | ("stop", "exited") =>
+                    return empty_response(304),
+                ("start", _) | ("restart", _) | ("unpause", "paused") => "running",
+                ("stop", _) | ("kill", "running") | ("kill", "paused") => "exited",
+                ("pause", "running") => "paused",
+                ("kill", _) | ("pause", _) | ("unpause", _) =>
+                    return error_response(409, format!("Container {} is not in a state to {}", id, action).as_str()),
+                _ => return error_response(404, "page not found")
+            };
+            if let Some(object) = container.as_object_mut() {
+                object.insert(String::from("State"), json!(next));
+            }
+            empty_response(204)
+        }
+        ("POST", ["containers", id, "rename"]) => {
+            let name = match request.query.iter().find(|&&(ref key, _)| key == "name") {
+                Some(&(_, ref name)) => format!("/{}", name),
+                None => return error_response(400, "name is required")
+            };
+            let taken = state.containers.iter().any(|container| {
+                container.get("Names").and_then(|names| names.as_array())
+                    .map_or(false, |names| names.iter().any(|known| known.as_str() == Some(name.as_str())))
+            });
+            if taken {
+                return error_response(409, format!("Conflict. The container name \"{}\" is already in use.", name).as_str());
+            }
+            match find(&mut state.containers, "Id", id) {
+                Some(container) => {
+                    if let Some(object) = container.as_object_mut() {
+                        object.insert(String::from("Names"), json!([name]));
+                    }
+                    empty_response(204)
+                }
+                None => error_response(404, format!("No such container: {}", id).as_str())
+            }
+        }
+        ("DELETE", ["containers", id]) => {
+            let force = request.query.iter().any(|&(ref key, ref value)| key == "force" && value == "1");
+            let running = match find(&mut state.containers, "Id", id) {
+                Some(container) => container.get("State").and_then(|value| value.as_str()) == Some("running"),
+                None => return error_response(404, format!("No such container: {}", id).as_str())
+            };
+            if running && !force {
+                return error_response(409, format!("You cannot remove a running container {}. Stop the container before attempting removal or force remove", id).as_str());
+            }
+            state.containers.retain(|container| container.get("Id").and_then(|value| value.as_str()) != Some(*id));
+            empty_response(204)
+        }
+        ("POST", ["containers", id, "attach"]) => match find(&mut state.containers, "Id", id) {
+            Some(_) => error_response(400, "attach needs an upgraded connection"),
+            None => error_response(404, format!("No such container: {}", id).as_str())
+        },
+        ("POST", ["containers", id, "exec"]) => {
+            let status = match find(&mut state.containers, "Id", id) {
+                Some(container) => String::from(container.get("State").and_then(|value| value.as_str()).unwrap_or("")),
+                None => return error_response(404, format!("No such container: {}", id).as_str())
+            };
+            if !status.is_empty() && status != "running" {
+                return error_response(409, format!("Container {} is not running", id).as_str());
+            }
+            let config : Value = match request.body.as_ref().map(|body| serde_json::from_slice(body)) {
+                Some(Ok(config)) => config,
+                _ => return error_response(400, "invalid JSON")
+            };
+            if config.get("Cmd").and_then(|cmd| cmd.as_array()).map_or(true, |cmd| cmd.is_empty()) {
+                return error_response(400, "No exec command specified");
+            }
+
+            let exec_id = state.next_id();
+            state.execs.push(json!({
+                "ID": exec_id,
+                "ContainerID": id,
+                "Running": false,
+                "OpenStdin": config.get("AttachStdin").cloned().unwrap_or(Value::Bool(false)),
+                "ProcessConfig": {
+                    "tty": config.get("Tty").cloned().unwrap_or(Value::Bool(false)),
+                    "entrypoint": config["Cmd"][0],
+                    "arguments": config["Cmd"].as_array().map(|cmd| cmd[1..].to_vec()).unwrap_or(Vec::new())
+                }
+            }));
+            json_response(201, &json!({"Id": exec_id}))
+        }
+        ("POST", ["exec", id, "start"]) => match find(&mut state.execs, "ID", id) {
+            Some(_) => error_response(400, "exec start needs an upgraded connection"),
+            None => error_response(404, format!("No such exec instance: {}", id).as_str())
+        },
+        ("GET", ["exec", id, "json"]) => match find(&mut state.execs, "ID", id) {
+            Some(exec) => json_response(200, exec),
+            None => error_response(404, format!("No such exec instance: {}", id).as_str())
+        },
+        ("GET", ["images", "json"]) => json_response(200, &Value::Array(state.images.clone())),
+        ("GET", ["networks"]) => json_response(200, &Value::Array(state.networks.clone())),
+        ("GET", ["volumes"]) => json_response(200, &json!({
+            "Volumes": state.volumes,
+            "Warnings": null
+        })),
+        ("GET", ["tasks"]) => json_response(200, &Value::Array(state.tasks.clone())),
+        ("GET", ["swarm"]) => match state.swarm {
+            Some(ref swarm) => json_response(200, swarm),
+            None => error_response(503, "This node is not a swarm manager.")
+        },
+        ("GET", ["secrets"]) => json_response(200, &Value::Array(state.secrets.clone())),
+        ("POST", ["secrets", "create"]) => {
+            let spec : Value = match request.body.as_ref().map(|body| serde_json::from_slice(body)) {
+                Some(Ok(spec)) => spec,
+                _ => return error_response(400, "invalid JSON")
+            };
+            let name = spec.get("Name").and_then(|name| name.as_str()).map(String::from);
+            let exists = state.secrets.iter().any(|secret| {
+                secret.pointer("/Spec/Name").and_then(|value| value.as_str()) == name.as_ref().map(|name| name.as_str())
+            });
+            if exists {
+                return error_response(409, "secret already exists");
+            }
+
+            let id = state.next_id();
+            state.secrets.push(json!({
+                "ID": id,
+                "Version": { "Index": 1 },
+                "Spec": spec
+            }));
+            json_response(201, &json!({ "ID": id }))
+        }
+        ("GET", ["secrets", id]) => match find(&mut state.secrets, "ID", id) {
+            Some(secret) => json_response(200, secret),
+            None => error_response(404, format!("secret {} not found", id).as_str())
+        },
+        ("POST", ["secrets", id, "update"]) => {
+            let spec : Value = match request.body.as_ref().map(|body| serde_json::from_slice(body)) {
+                Some(Ok(spec)) => spec,
+                _ => return error_response(400, "invalid JSON")
+            };
+            match find(&mut state.secrets, "ID", id) {
+                Some(secret) => {
+                    let index = secret.pointer("/Version/Index").and_then(|index| index.as_i64()).unwrap_or(0);
+                    if let Some(object) = secret.as_object_mut() {
+                        object.insert(String::from("Spec"), spec);
+                        object.insert(String::from("Version"), json!({ "Index": index + 1 }));
+                    }
+                    empty_response(200)
+                }
+                None => error_response(404, format!("secret {} not found", id).as_str())
+            }
+        }
+        ("DELETE", ["secrets", id]) => {
+            let before = state.secrets.len();
+            state.secrets.retain(|secret| secret.get("ID").and_then(|value| value.as_str()) != Some(*id));
+            if state.secrets.len() < before {
+                empty_response(204)
+            } else {
+                error_response(404, format!("secret {} not found", id).as_str())
+            }
+        }
+        _ => error_response(404, "page not found")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn split_version() {
+        use mock::split_version;
+        assert!(split_version("/v1.27/containers/json") == (Some("1.27"), "/containers/json"));
+        assert!(split_version("/_ping") == (None, "/_ping"));
+        assert!(split_version("/volumes") == (None, "/volumes"));
+    }
+
+    #[test]
+    fn scripted_response() {
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.respond("GET", "/images/json", 500, "{\"message\":\"boom\"}");
+        match daemon.client().images().all() {
+            Err(DockerError::ServerError { message, .. }) => assert!(message == "boom"),
+            other => panic!("expected server error, got {:?}", other)
+        }
+        assert!(daemon.requests()[0].path == "/v1.27/images/json");
+    }
+
+    #[test]
+    fn rejects_too_new_version() {
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.set_api_version("1.24");
+        match daemon.client().images().all() {
+            Err(DockerError::BadParameter { .. }) => (),
+            other => panic!("expected bad parameter, got {:?}", other)
+        }
+    }
+}
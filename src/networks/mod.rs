@@ -34,9 +34,12 @@ impl<'a> NetworksClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use networks::NetworksClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_network(&json!({"Name": "bridge", "Id": "1", "Driver": "bridge"}));
+        daemon.add_network(&json!({"Name": "host", "Id": "2", "Driver": "host"}));
+        let client = daemon.client();
         let image_client = NetworksClient::new(&client);
         let networks = image_client.all();
         assert!(networks.is_ok());
@@ -22,26 +22,34 @@ use self::schema::{Secret, SecretSpec};
 
 endpoint!(SecretsClient);
 
+// Secrets were added with API 1.25
+pub const SECRETS_API_VERSION: &'static str = "1.25";
+
 impl<'a> SecretsClient<'a> {
     pub fn all(&self) -> Result<Vec<Secret>, DockerError> {
+        self.client.require_api_version(SECRETS_API_VERSION)?;
         get_vector(self.client, "secrets")
     }
 
     pub fn inspect(&self, id: &String) -> Result<Secret, DockerError> {
-        let url = format!("secrects/{}", id);
+        self.client.require_api_version(SECRETS_API_VERSION)?;
+        let url = format!("secrets/{}", id);
         get(self.client, url.as_str())
     }
 
     pub fn create(&self, spec: &SecretSpec) -> Result<(), DockerError> {
+        self.client.require_api_version(SECRETS_API_VERSION)?;
         post(self.client, "secrets/create", spec)
     }
 
     pub fn update(&self, id: &String, spec: &SecretSpec) -> Result<(), DockerError> {
+        self.client.require_api_version(SECRETS_API_VERSION)?;
         let url = format!("secrets/{}/update", id);
         post(self.client, url.as_str(), spec)
     }
 
     pub fn delete(&self, id: &String) -> Result<(), DockerError> {
+        self.client.require_api_version(SECRETS_API_VERSION)?;
         let url = format!("secrets/{}", id);
         delete(self.client, url.as_str())
     }
@@ -52,22 +60,24 @@ impl<'a> SecretsClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use secrets::SecretsClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
         let secret_client = SecretsClient::new(&client);
         let secrets = secret_client.all();
         assert!(secrets.is_ok());
-//        assert!(secrets.unwrap().len()==0);
+        assert!(secrets.unwrap().len()==0);
     }
 
     #[test]
     fn create_and_delete_secret() {
         use std::collections::HashMap;
-        use Client;
         use secrets::schema::SecretSpec;
         use secrets::SecretsClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
         let secret_client = SecretsClient::new(&client);
 
         let secret = SecretSpec{
@@ -76,43 +86,110 @@ mod tests {
                         data: Some(String::from("VEhJUyBJUyBOT1QgQSBSRUFMIENFUlRJRklDQVRFCg=="))
                      };
         let result = secret_client.create(&secret);
+        assert!(result.is_ok());
 
-        let secrets = secret_client.all();
-        assert!(secrets.is_ok());
-        assert!(secrets.unwrap().len()==0);        
+        let secrets = secret_client.all().unwrap();
+        assert!(secrets.len()==1);
+
+        let id = secrets[0].id.clone().unwrap();
+        assert!(secret_client.delete(&id).is_ok());
+        assert!(secret_client.all().unwrap().len()==0);
+    }
+
+    #[test]
+    fn create_duplicate_secret() {
+        use secrets::schema::SecretSpec;
+        use secrets::SecretsClient;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
+        let secret_client = SecretsClient::new(&client);
+
+        let secret = SecretSpec{
+                        name: Some(String::from("MySecret")),
+                        labels: None,
+                        data: Some(String::from("VEhJUyBJUyBOT1QgQSBSRUFMIENFUlRJRklDQVRFCg=="))
+                     };
+        assert!(secret_client.create(&secret).is_ok());
+        match secret_client.create(&secret) {
+            Err(DockerError::Conflict { .. }) => (),
+            other => panic!("expected conflict, got {:?}", other)
+        }
     }
 
     #[test]
     fn delete_secret() {
         use secrets::SecretsClient;
-        use Client;
-        let client = Client::from_env();
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_secret(&json!({"ID": "o12uix0o96y2px62r2i8l6wpt", "Spec": {"Name": "MySecret"}}));
+        let client = daemon.client();
         let secret_client = SecretsClient::new(&client);
 
         let result = secret_client.delete(&String::from("o12uix0o96y2px62r2i8l6wpt"));
         assert!(result.is_ok());
-    }        
-    
+
+        match secret_client.delete(&String::from("o12uix0o96y2px62r2i8l6wpt")) {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+    }
+
     #[test]
     fn inspect_secret() {
         use secrets::SecretsClient;
-        use Client;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_secret(&json!({"ID": "gucl9mst94yfe2yvkpmhz0hr2", "Spec": {"Name": "MySecret"}}));
+        let client = daemon.client();
         let secret_client = SecretsClient::new(&client);
-        
+
         let result = secret_client.inspect(&String::from("gucl9mst94yfe2yvkpmhz0hr2"));
-        assert!(result.is_ok());
+        assert!(result.unwrap().id == Some(String::from("gucl9mst94yfe2yvkpmhz0hr2")));
+    }
+
+    #[test]
+    fn inspect_missing_secret() {
+        use secrets::SecretsClient;
+        use error::DockerError;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
+        let secret_client = SecretsClient::new(&client);
+
+        match secret_client.inspect(&String::from("doesnotexist")) {
+            Err(DockerError::NotFound { .. }) => (),
+            other => panic!("expected not found, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn secrets_need_api_1_25() {
+        use secrets::SecretsClient;
+        use error::DockerError;
+        use Client;
+        let client = Client::builder().api_version("1.24").build().unwrap();
+        let secret_client = SecretsClient::new(&client);
+
+        match secret_client.all() {
+            Err(DockerError::UnsupportedApiVersion { .. }) => (),
+            other => panic!("expected unsupported api version, got {:?}", other)
+        }
     }
 
     #[test]
     fn update_secret() {
         use std::collections::HashMap;
         use secrets::SecretsClient;
-        use Client;
         use secrets::schema::SecretSpec;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_secret(&json!({"ID": "gucl9mst94yfe2yvkpmhz0hr2", "Spec": {"Name": "MySecret"}}));
+        let client = daemon.client();
         let secret_client = SecretsClient::new(&client);
-        
+
         let secret = SecretSpec{
                         name: Some(String::from("MySecretUpdated")),
                         labels: Option::Some(HashMap::new()),
@@ -121,6 +198,6 @@ mod tests {
 
         let result = secret_client.update(&String::from("gucl9mst94yfe2yvkpmhz0hr2"), &secret);
         assert!(result.is_ok());
+        assert!(daemon.state().secrets[0].pointer("/Spec/Name").unwrap() == "MySecretUpdated");
     }
-    
 }
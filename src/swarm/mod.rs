@@ -33,11 +33,26 @@ impl<'a> SwarmClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use swarm::SwarmClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.set_swarm(&json!({"ID": "swarm1", "Spec": {"Name": "default"}, "JoinTokens": {}}));
+        let client = daemon.client();
         let swarm_client = SwarmClient::new(&client);
         let swarm = swarm_client.get();
         assert!(swarm.is_ok());
     }
+
+    #[test]
+    fn not_a_manager() {
+        use error::DockerError;
+        use swarm::SwarmClient;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
+        match SwarmClient::new(&client).get() {
+            Err(DockerError::ServerError { status, .. }) => assert!(status == 503),
+            other => panic!("expected server error, got {:?}", other)
+        }
+    }
 }
@@ -0,0 +1,109 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod schema;
+
+use super::common::*;
+use super::Client;
+use super::error::DockerError;
+
+use self::schema::{Event, Version};
+
+endpoint!(SystemClient);
+
+impl<'a> SystemClient<'a> {
+    pub fn ping(&self) -> Result<String, DockerError> {
+        get_text(self.client, "_ping")
+    }
+
+    pub fn version(&self) -> Result<Version, DockerError> {
+        get(self.client, "version")
+    }
+
+    // Live stream of daemon events, it only ends when the connection
+    // breaks or the stream is dropped
+    pub fn events(&self) -> Result<JsonStream<Event>, DockerError> {
+        Ok(get_stream(self.client, "events")?.json_stream())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {
+        use system::SystemClient;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let client = daemon.client();
+        let system_client = SystemClient::new(&client);
+        assert!(system_client.ping().unwrap() == "OK");
+        assert!(system_client.version().is_ok());
+    }
+
+    #[test]
+    fn events() {
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_event(&json!({"Type": "container", "Action": "create", "Actor": {"ID": "abc"}, "time": 1}));
+        daemon.add_event(&json!({"Type": "container", "Action": "start", "Actor": {"ID": "abc"}, "time": 2}));
+        let client = daemon.client();
+
+        // the mock never ends the stream, dropping it has to
+        let mut events = client.system().events().unwrap();
+        let create = events.next().unwrap().unwrap();
+        let start = events.next().unwrap().unwrap();
+        assert!(create.action.unwrap() == "create");
+        assert!(start.actor.unwrap().id.unwrap() == "abc");
+    }
+
+    #[test]
+    fn negotiate_version() {
+        use config::MAX_API_VERSION;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        let mut client = daemon.client_builder().api_version("1.12").build().unwrap();
+        client.negotiate_version().unwrap();
+        assert!(client.api_version() == MAX_API_VERSION);
+        assert!(client.require_api_version("1.25").is_ok());
+    }
+
+    #[test]
+    fn negotiate_unlocks_newer_features() {
+        use containers::LogOptions;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.set_api_version("1.40");
+        daemon.add_container(&json!({"Id": "abc", "State": "exited", "Config": {"Image": "busybox", "Tty": false}}));
+        daemon.add_log("abc", "stdout", "done\n");
+
+        let mut client = daemon.client();
+        assert!(client.containers().logs("abc", &LogOptions::new().until(1483228800)).is_err());
+        client.negotiate_version().unwrap();
+        assert!(client.api_version() == "1.35");
+        let logs = client.containers().logs("abc", &LogOptions::new().until(1483228800)).unwrap();
+        assert!(logs.count() == 1);
+    }
+
+    #[test]
+    fn negotiate_down_to_older_daemon() {
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.set_api_version("1.24");
+        let client = daemon.client_builder().negotiate_version().build().unwrap();
+        assert!(client.api_version() == "1.24");
+        assert!(client.require_api_version("1.25").is_err());
+        assert!(client.system().version().unwrap().api_version == "1.24");
+    }
+}
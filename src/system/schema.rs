@@ -0,0 +1,96 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Version {
+   #[serde(rename = "Version")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub version : Option<String>,
+
+   // Newest API version the daemon speaks
+   #[serde(rename = "ApiVersion")]
+   pub api_version : String,
+
+   // Oldest API version the daemon still accepts
+   #[serde(rename = "MinAPIVersion")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub min_api_version : Option<String>,
+
+   #[serde(rename = "GitCommit")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub git_commit : Option<String>,
+
+   #[serde(rename = "GoVersion")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub go_version : Option<String>,
+
+   #[serde(rename = "Os")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub os : Option<String>,
+
+   #[serde(rename = "Arch")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub arch : Option<String>,
+
+   #[serde(rename = "KernelVersion")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub kernel_version : Option<String>,
+
+   #[serde(rename = "Experimental")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub experimental : Option<bool>,
+
+   #[serde(rename = "BuildTime")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub build_time : Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventActor {
+   #[serde(rename = "ID")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub id : Option<String>,
+
+   #[serde(rename = "Attributes")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub attributes : Option<HashMap<String,String>>
+}
+
+// Something that happened on the daemon, e.g. a container that started
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Event {
+   // Kind of object the event is about, e.g. `container` or `image`
+   #[serde(rename = "Type")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub event_type : Option<String>,
+
+   #[serde(rename = "Action")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub action : Option<String>,
+
+   #[serde(rename = "Actor")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub actor : Option<EventActor>,
+
+   // Unix timestamp
+   #[serde(rename = "time")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub time : Option<i64>,
+
+   #[serde(rename = "timeNano")]
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub time_nano : Option<i64>
+}
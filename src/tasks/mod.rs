@@ -33,9 +33,11 @@ impl<'a> TasksClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use tasks::TasksClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_task(&json!({"ID": "task1", "Name": "web.1"}));
+        let client = daemon.client();
         let tasks_client = TasksClient::new(&client);
         let tasks = tasks_client.get();
         assert!(tasks.is_ok());
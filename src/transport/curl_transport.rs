@@ -0,0 +1,462 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+
+use curl::easy::{Easy, List};
+use curl_sys;
+use libc;
+
+use config::{port, Config, Host};
+use error::DockerError;
+use super::{parse_header, Request, Response, StreamingResponse, Transport};
+use super::hijack::{handshake, CloseWrite, HijackedStream};
+use super::pool::Pool;
+
+// Chunks buffered between the transfer thread and the reader before
+// the transfer waits for the reader to catch up
+const STREAM_BUFFER: usize = 16;
+
+// Default transport, talks to the daemon through libcurl over the
+// unix socket or tcp with optional TLS
+pub struct CurlTransport {
+    base_url: String,
+    config: Config,
+    // shared with the threads running streamed transfers
+    pool: Arc<Pool>
+}
+
+impl CurlTransport {
+    pub fn new(config: &Config) -> CurlTransport {
+        CurlTransport::with_base_url(config, config.host_url().as_str())
+    }
+
+    // Request paths get appended to `base_url` instead of the host
+    // url derived from the config
+    pub fn with_base_url(config: &Config, base_url: &str) -> CurlTransport {
+        CurlTransport {
+            base_url: String::from(if base_url.ends_with('/') {
+                &base_url[..base_url.len() - 1]
+            } else {
+                base_url
+            }),
+            config: config.clone(),
+            pool: Arc::new(Pool::new())
+        }
+    }
+}
+
+// Sets up everything but the transfer callbacks
+fn configure(config: &Config, base_url: &str, curl: &mut Easy, request: &Request) -> Result<(), DockerError> {
+    let real_url = format!("{}{}", base_url, request.path_and_query());
+
+    if let Host::Ssh(ref address) = config.host {
+        return Err(DockerError::Docker(format!("curl can't reach ssh://{}", address)));
+    }
+
+    // handles are reused between calls, drop whatever the
+    // previous request has configured
+    curl.reset();
+    if let Host::Unix(ref path) = config.host {
+        curl.unix_socket(path.as_str())?;
+    }
+    if let Some(ref tls) = config.tls {
+        if let Some(ref ca) = tls.ca {
+            curl.cainfo(ca)?;
+        }
+        if let Some(ref cert) = tls.cert {
+            curl.ssl_cert(cert)?;
+        }
+        if let Some(ref key) = tls.key {
+            curl.ssl_key(key)?;
+        }
+        curl.ssl_verify_peer(tls.verify)?;
+        curl.ssl_verify_host(tls.verify_hostname)?;
+    }
+    if let Some(entry) = config.resolve_entry()? {
+        let mut resolve = List::new();
+        resolve.append(entry.as_str())?;
+        curl.resolve(resolve)?;
+    }
+    if let Some(timeout) = config.connect_timeout {
+        curl.connect_timeout(timeout)?;
+    }
+    curl.url(real_url.as_str())?;
+
+    let mut headers = List::new();
+    for &(ref name, ref value) in request.headers.iter() {
+        headers.append(format!("{}: {}", name, value).as_str())?;
+    }
+
+    let length = request.body.as_ref().map_or(0, |body| body.len() as u64);
+    match (request.method.as_str(), request.body.is_some()) {
+        ("GET", false) => curl.get(true)?,
+        ("HEAD", false) => curl.nobody(true)?,
+        ("POST", _) => {
+            curl.post(true)?;
+            curl.post_field_size(length)?;
+        }
+        (method, true) => {
+            // a body is only sent along with post, the method is
+            // then swapped for the real one
+            curl.post(true)?;
+            curl.post_field_size(length)?;
+            curl.custom_request(method)?;
+        }
+        (method, false) => curl.custom_request(method)?
+    }
+    curl.http_headers(headers)?;
+
+    Ok(())
+}
+
+impl Transport for CurlTransport {
+    fn request(&self, request: &Request) -> Result<Response, DockerError> {
+        let mut result = Vec::new();
+        let mut response_headers = Vec::new();
+        let mut curl = self.pool.checkout();
+        configure(&self.config, self.base_url.as_str(), &mut curl, request)?;
+        // only here, streams like events or followed logs may be quiet
+        // for as long as they like
+        if let Some(timeout) = self.config.read_timeout {
+            curl.low_speed_limit(1)?;
+            curl.low_speed_time(timeout)?;
+        }
+
+        let mut payload = match request.body {
+            Some(ref body) => body.as_slice(),
+            None => &[]
+        };
+
+        {
+            let mut transfer = curl.transfer();
+            transfer.read_function(|buf| {
+                Ok(payload.read(buf).unwrap_or(0))
+            })?;
+            transfer.header_function(|line| {
+                if let Some(header) = parse_header(line) {
+                    response_headers.push(header);
+                }
+                true
+            })?;
+            transfer.write_function(|data| {
+                result.extend_from_slice(data);
+                Ok(data.len())
+            })?;
+            transfer.perform()?;
+        }
+
+        Ok(Response {
+            status: curl.response_code()?,
+            headers: response_headers,
+            body: result
+        })
+    }
+
+    // The transfer runs on its own thread and hands the body over in
+    // chunks as it arrives. Dropping the body aborts the transfer.
+    fn stream(&self, request: &Request) -> Result<StreamingResponse, DockerError> {
+        let (sender, receiver) = sync_channel(STREAM_BUFFER);
+        let config = self.config.clone();
+        let base_url = self.base_url.clone();
+        let request = request.clone();
+        let pool = self.pool.clone();
+        let closed = Arc::new(AtomicBool::new(false));
+        let cancelled = closed.clone();
+
+        thread::spawn(move || {
+            // goes back to the pool once the transfer is over
+            let mut curl = pool.checkout();
+            let result = configure(&config, base_url.as_str(), &mut curl, &request)
+                .and_then(|_| transfer_to(&mut curl, &request, &sender, &cancelled));
+            let _ = sender.send(Event::Done(result));
+        });
+
+        match receiver.recv() {
+            Ok(Event::Head(status, headers)) => Ok(StreamingResponse {
+                status: status,
+                headers: headers,
+                body: Box::new(ChannelReader {
+                    receiver: receiver,
+                    chunk: Vec::new(),
+                    position: 0,
+                    finished: false,
+                    closed: closed
+                })
+            }),
+            Ok(Event::Done(Err(error))) => Err(error),
+            _ => Err(DockerError::Docker(String::from("connection closed before the response arrived")))
+        }
+    }
+
+    fn hijack(&self, request: &Request) -> Result<HijackedStream, DockerError> {
+        match (&self.config.host, &self.config.tls) {
+            (&Host::Unix(ref path), _) => {
+                let stream = UnixStream::connect(path)?;
+                handshake(stream.try_clone()?, stream, request, "localhost")
+            }
+            (&Host::Tcp(ref address), &None) => {
+                let stream = TcpStream::connect(address.as_str())?;
+                handshake(stream.try_clone()?, stream, request, address.as_str())
+            }
+            (&Host::Ssh(ref address), _) => Err(DockerError::Docker(format!("curl can't reach ssh://{}", address))),
+            (&Host::Tcp(ref address), &Some(ref tls)) => {
+                // curl does the TLS handshake and then only moves bytes
+                let mut curl = Easy::new();
+                configure(&self.config, self.base_url.as_str(), &mut curl, request)?;
+                curl.connect_only(true)?;
+                curl.perform()?;
+
+                let host = match tls.server_name {
+                    Some(ref name) => format!("{}:{}", name, port(address)),
+                    None => address.clone()
+                };
+                let socket = last_socket(&curl)?;
+                let connection = CurlConnection { curl: Arc::new(Mutex::new(curl)), socket: socket };
+                handshake(connection.clone(), connection, request, host.as_str())
+            }
+        }
+    }
+}
+
+// Socket of the connection a `connect_only` transfer has set up
+fn last_socket(curl: &Easy) -> Result<libc::c_int, DockerError> {
+    let mut socket : libc::c_long = -1;
+    let code = unsafe {
+        curl_sys::curl_easy_getinfo(curl.raw(), curl_sys::CURLINFO_LASTSOCKET, &mut socket as *mut libc::c_long)
+    };
+    if code != curl_sys::CURLE_OK || socket < 0 {
+        return Err(DockerError::Docker(String::from("curl has no socket for the connection")));
+    }
+    Ok(socket as libc::c_int)
+}
+
+// Blocks until `socket` is ready for `events` or has been closed
+fn wait_for(socket: libc::c_int, events: libc::c_short) -> io::Result<()> {
+    let mut poll = libc::pollfd { fd: socket, events: events, revents: 0 };
+    loop {
+        if unsafe { libc::poll(&mut poll, 1, -1) } >= 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+// A connected curl handle used as a plain socket. Curl only offers
+// non blocking reads and writes on it, when they can't make progress
+// the socket is waited on without holding the handle, which leaves
+// it to the other direction in the meantime.
+#[derive(Clone)]
+struct CurlConnection {
+    curl: Arc<Mutex<Easy>>,
+    socket: libc::c_int
+}
+
+impl Read for CurlConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.curl.lock().unwrap().recv(buf) {
+                Ok(count) => return Ok(count),
+                Err(ref error) if error.is_again() => (),
+                Err(error) => return Err(io::Error::new(io::ErrorKind::Other, error))
+            }
+            wait_for(self.socket, libc::POLLIN)?;
+        }
+    }
+}
+
+impl Write for CurlConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            match self.curl.lock().unwrap().send(buf) {
+                Ok(count) => return Ok(count),
+                Err(ref error) if error.is_again() => (),
+                Err(error) => return Err(io::Error::new(io::ErrorKind::Other, error))
+            }
+            wait_for(self.socket, libc::POLLOUT)?;
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CloseWrite for CurlConnection {
+    // Curl can't close one direction of a TLS connection, the socket
+    // under it is shut down instead like the docker cli does. The
+    // daemon reads that as the end of stdin.
+    fn close_write(&mut self) -> io::Result<()> {
+        // keeps a write in progress from racing the shutdown
+        let _curl = self.curl.lock().unwrap();
+        if unsafe { libc::shutdown(self.socket, libc::SHUT_WR) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+enum Event {
+    Head(u32, Vec<(String, String)>),
+    Data(Vec<u8>),
+    Done(Result<(), DockerError>)
+}
+
+fn status_code(line: &[u8]) -> Option<u32> {
+    let line = String::from_utf8_lossy(line);
+    if !line.starts_with("HTTP/") {
+        return None;
+    }
+    line.split_whitespace().nth(1).and_then(|code| code.parse().ok())
+}
+
+fn transfer_to(curl: &mut Easy, request: &Request, sender: &SyncSender<Event>,
+               cancelled: &AtomicBool) -> Result<(), DockerError> {
+    let mut payload = match request.body {
+        Some(ref body) => body.as_slice(),
+        None => &[]
+    };
+    let mut status = 0;
+    let mut headers = Vec::new();
+
+    // the progress callback also runs while the daemon sends nothing,
+    // which lets an idle stream notice that its reader is gone
+    curl.progress(true)?;
+    let mut transfer = curl.transfer();
+    transfer.progress_function(|_, _, _, _| !cancelled.load(Ordering::SeqCst))?;
+    transfer.read_function(|buf| {
+        Ok(payload.read(buf).unwrap_or(0))
+    })?;
+    transfer.header_function(|line| {
+        if let Some(code) = status_code(line) {
+            // a new status line, e.g. after `100 Continue`
+            status = code;
+            headers.clear();
+        } else if line == b"\r\n" || line == b"\n" {
+            if status >= 200 {
+                let head = Event::Head(status, headers.drain(..).collect());
+                return sender.send(head).is_ok();
+            }
+        } else if let Some(header) = parse_header(line) {
+            headers.push(header);
+        }
+        true
+    })?;
+    transfer.write_function(|data| {
+        // a short count makes curl abort once the reader is gone
+        match sender.send(Event::Data(data.to_vec())) {
+            Ok(_) => Ok(data.len()),
+            Err(_) => Ok(0)
+        }
+    })?;
+    transfer.perform()?;
+
+    Ok(())
+}
+
+struct ChannelReader {
+    receiver: Receiver<Event>,
+    chunk: Vec<u8>,
+    position: usize,
+    finished: bool,
+    closed: Arc<AtomicBool>
+}
+
+impl Drop for ChannelReader {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            match self.receiver.recv() {
+                Ok(Event::Data(chunk)) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Ok(Event::Done(Err(error))) => {
+                    self.finished = true;
+                    return Err(io::Error::new(io::ErrorKind::Other, error));
+                }
+                Ok(Event::Head(..)) => (),
+                Ok(Event::Done(Ok(()))) | Err(_) => self.finished = true
+            }
+        }
+
+        let count = (&self.chunk[self.position..]).read(buf)?;
+        self.position += count;
+        Ok(count)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn streams_use_pooled_handles() {
+        use std::io::Read;
+        use std::thread;
+        use std::time::Duration;
+        use mock::MockDaemon;
+        use transport::{CurlTransport, Request, Transport};
+        let daemon = MockDaemon::start();
+        daemon.respond("GET", "/containers/abc/export", 200, "not really a tar");
+        let transport = CurlTransport::new(daemon.client().config());
+
+        for _ in 0..3 {
+            let mut response = transport.stream(&Request::new("GET", "/containers/abc/export")).unwrap();
+            response.read_to_end(&mut Vec::new()).unwrap();
+            // the transfer thread checks the handle in after the reader
+            // has seen the end of the body
+            for _ in 0..100 {
+                if transport.pool.idle() == 1 {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            assert!(transport.pool.idle() == 1);
+        }
+    }
+
+    #[test]
+    fn quiet_streams_outlive_the_read_timeout() {
+        use std::thread;
+        use std::time::Duration;
+        use containers::LogOptions;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_container(&json!({"Id": "abc", "State": "running"}));
+        let client = daemon.client_builder().read_timeout(Duration::from_secs(1)).build().unwrap();
+
+        let mut lines = client.containers().log_lines("abc", &LogOptions::new().follow(true).tty(false)).unwrap();
+        thread::sleep(Duration::from_millis(2500));
+        daemon.add_log("abc", "stdout", "late\n");
+        assert!(lines.next().unwrap().unwrap().output.as_bytes() == b"late\n");
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Docker switches attach and interactive exec connections from HTTP to
+// a raw byte stream once it answered `101 UPGRADED`. Curl can't hand
+// out such a connection, so the request is written with the helpers
+// from `http` onto a socket the transport opened itself.
+
+use std::io::{self, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::os::unix::net::UnixStream;
+
+use error::DockerError;
+use http;
+use super::Request;
+
+// Write half of a connection that can be closed on its own, so the
+// container sees the end of stdin while its output is still read
+pub trait CloseWrite : Write + Send {
+    fn close_write(&mut self) -> io::Result<()>;
+}
+
+impl CloseWrite for UnixStream {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+impl CloseWrite for TcpStream {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+// Stdin of the container
+pub struct HijackedInput {
+    inner: Box<CloseWrite>
+}
+
+impl HijackedInput {
+    // Signals the end of input, output can still be read afterwards
+    pub fn close(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.inner.close_write()
+    }
+}
+
+impl Write for HijackedInput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Connection left over after the upgrade. Both halves can be moved to
+// different threads, e.g. to copy a terminal into `input` while
+// `output` is printed.
+pub struct HijackedStream {
+    pub status: u32,
+    pub headers: Vec<(String, String)>,
+    pub output: Box<Read + Send>,
+    pub input: HijackedInput
+}
+
+impl Read for HijackedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.output.read(buf)
+    }
+}
+
+impl Write for HijackedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.input.flush()
+    }
+}
+
+// Sends `request` asking for an upgrade and waits for the daemon to
+// agree. Error statuses come back as `DockerError`s.
+pub fn handshake<R, W>(reader: R, mut writer: W, request: &Request, host: &str) -> Result<HijackedStream, DockerError>
+    where R: Read + Send + 'static, W: CloseWrite + 'static
+{
+    let start = format!("{} {} HTTP/1.1", request.method, request.path_and_query());
+    let mut headers = vec![(String::from("Host"), String::from(host)),
+                           (String::from("Connection"), String::from("Upgrade")),
+                           (String::from("Upgrade"), String::from("tcp"))];
+    headers.extend(request.headers.iter().cloned());
+    let body = match request.body {
+        Some(ref body) => body.as_slice(),
+        None => &[]
+    };
+    http::write_message(&mut writer, start.as_str(), &headers, body)?;
+
+    let mut reader = BufReader::new(reader);
+    let head = match http::read_head(&mut reader)? {
+        Some(head) => head,
+        None => return Err(DockerError::Docker(String::from("connection closed before the upgrade")))
+    };
+    let status = head.start.split_whitespace().nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+
+    // older daemons answer `200 OK` and start streaming right away
+    if status == 0 || status >= 300 {
+        let until_eof = head.content_length().is_none() && !head.is_chunked();
+        let body = http::read_body(&mut reader, &head, until_eof)?;
+        return Err(DockerError::from_response(status, &body));
+    }
+
+    Ok(HijackedStream {
+        status: status,
+        headers: head.headers,
+        output: Box::new(reader),
+        input: HijackedInput { inner: Box::new(writer) }
+    })
+}
@@ -0,0 +1,305 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod pool;
+pub mod curl_transport;
+pub mod hijack;
+pub mod record;
+pub mod ssh;
+pub mod stream;
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json;
+
+use auth::{RegistryAuth, REGISTRY_AUTH_HEADER};
+use config::{Config, Host};
+use error::DockerError;
+
+pub use self::curl_transport::CurlTransport;
+pub use self::hijack::{HijackedInput, HijackedStream};
+pub use self::record::{RecordingTransport, ReplayTransport};
+pub use self::ssh::SshTransport;
+pub use self::stream::StreamingResponse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+
+    // Absolute path on the daemon, e.g. `/v1.27/containers/json`
+    pub path: String,
+
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u32,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>
+}
+
+// Sends a request to the daemon and hands back whatever it answered,
+// error statuses included. `Client` turns those into `DockerError`s.
+pub trait Transport : Send + Sync {
+    fn request(&self, request: &Request) -> Result<Response, DockerError>;
+
+    // Like `request` but hands the body out while it is still being
+    // received. Transports that can't do that buffer the whole body.
+    fn stream(&self, request: &Request) -> Result<StreamingResponse, DockerError> {
+        let response = self.request(request)?;
+        Ok(StreamingResponse {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(Cursor::new(response.body))
+        })
+    }
+
+    // Upgrades the connection to a raw stream, for attach and exec
+    fn hijack(&self, request: &Request) -> Result<HijackedStream, DockerError> {
+        Err(DockerError::Docker(format!("transport can't hijack the connection for {}", request.path)))
+    }
+}
+
+// Transport that reaches the host of `config`: ssh hosts through
+// the ssh command, everything else with curl
+pub fn for_config(config: &Config) -> Arc<Transport> {
+    match config.host {
+        Host::Ssh(ref address) => Arc::new(SshTransport::new(address, config)),
+        _ => Arc::new(CurlTransport::new(config))
+    }
+}
+
+impl Request {
+    pub fn new(method: &str, path: &str) -> Request {
+        Request {
+            method: String::from(method),
+            path: String::from(path),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn with_query(mut self, key: &str, value: &str) -> Request {
+        self.query.push((String::from(key), String::from(value)));
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Request {
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    // Raw body, e.g. a tar archive with `application/x-tar`
+    pub fn with_body(mut self, content_type: &str, body: Vec<u8>) -> Request {
+        self.headers.push((String::from("Content-Type"), String::from(content_type)));
+        self.body = Some(body);
+        self
+    }
+
+    pub fn with_json<T: Serialize>(self, payload: &T) -> Result<Request, DockerError> {
+        let body = serde_json::to_vec(payload).map_err(|error| DockerError::Json {
+            error: error,
+            payload: String::new()
+        })?;
+        Ok(self.with_body("application/json", body))
+    }
+
+    // Credentials for pulling from or pushing to a private registry
+    pub fn with_registry_auth(self, auth: &RegistryAuth) -> Result<Request, DockerError> {
+        let encoded = auth.encode()?;
+        Ok(self.with_header(REGISTRY_AUTH_HEADER, encoded.as_str()))
+    }
+
+    // Path with the percent encoded query appended
+    pub fn path_and_query(&self) -> String {
+        if self.query.is_empty() {
+            return self.path.clone();
+        }
+
+        let query : Vec<String> = self.query.iter()
+            .map(|&(ref key, ref value)| format!("{}={}", encode(key), encode(value)))
+            .collect();
+        format!("{}?{}", self.path, query.join("&"))
+    }
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn text(self) -> Result<String, DockerError> {
+        Ok(String::from_utf8(self.body)?)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter()
+        .find(|&&(ref key, _)| key.eq_ignore_ascii_case(name))
+        .map(|&(_, ref value)| value.as_str())
+}
+
+// Splits a raw `Name: value` header line
+pub fn parse_header(line: &[u8]) -> Option<(String, String)> {
+    let line = String::from_utf8_lossy(line);
+    let mut parts = line.splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(value)) => Some((String::from(name.trim()), String::from(value.trim()))),
+        _ => None
+    }
+}
+
+// Percent encodes everything outside the unreserved characters
+pub fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte as char {
+            c if c.is_ascii_alphanumeric() => encoded.push(c),
+            '-' | '_' | '.' | '~' => encoded.push(byte as char),
+            _ => encoded.push_str(format!("%{:02X}", byte).as_str())
+        }
+    }
+    encoded
+}
+
+// Reverses `encode`, also turning `+` into a space
+pub fn decode(value: &str) -> String {
+    fn hex(byte: u8) -> Option<u8> {
+        (byte as char).to_digit(16).map(|digit| digit as u8)
+    }
+
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                decoded.push(high * 16 + low);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Splits a raw query string into decoded pairs
+pub fn parse_query(query: &str) -> Vec<(String, String)> {
+    query.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("");
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use error::DockerError;
+    use transport::{Request, Response, Transport};
+
+    // Answers every request with the same canned response and
+    // remembers what it was asked
+    struct StaticTransport {
+        status: u32,
+        body: &'static str,
+        requests: Mutex<Vec<Request>>
+    }
+
+    impl Transport for StaticTransport {
+        fn request(&self, request: &Request) -> Result<Response, DockerError> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(Response {
+                status: self.status,
+                headers: Vec::new(),
+                body: Vec::from(self.body.as_bytes())
+            })
+        }
+    }
+
+    #[test]
+    fn encodes_query() {
+        let mut request = Request::new("GET", "/v1.27/containers/json");
+        request.query.push((String::from("filters"), String::from("{\"label\":[\"team=x\"]}")));
+        request.query.push((String::from("all"), String::from("1")));
+        assert!(request.path_and_query() ==
+                "/v1.27/containers/json?filters=%7B%22label%22%3A%5B%22team%3Dx%22%5D%7D&all=1");
+    }
+
+    #[test]
+    fn decodes_query() {
+        use transport::parse_query;
+        let query = parse_query("filters=%7B%22label%22%3A%5B%22team%3Dx%22%5D%7D&all=1&name=a+b&bad=%zz");
+        assert!(query[0] == (String::from("filters"), String::from("{\"label\":[\"team=x\"]}")));
+        assert!(query[1] == (String::from("all"), String::from("1")));
+        assert!(query[2] == (String::from("name"), String::from("a b")));
+        assert!(query[3] == (String::from("bad"), String::from("%zz")));
+    }
+
+    #[test]
+    fn in_memory_transport() {
+        use std::sync::Arc;
+        use Client;
+        use config::Config;
+
+        let transport = Arc::new(StaticTransport {
+            status: 200,
+            body: "[{\"Id\":\"abc\",\"Names\":[\"/web\"]}]",
+            requests: Mutex::new(Vec::new())
+        });
+        let client = Client::with_transport(Config::default(), transport.clone());
+        let containers = client.containers().all().unwrap();
+        assert!(containers[0].id == Some(String::from("abc")));
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0].method == "GET");
+        assert!(requests[0].path == "/v1.27/containers/json");
+        assert!(requests[0].header("User-Agent").unwrap().starts_with("docker-rust/"));
+    }
+
+    #[test]
+    fn error_status_from_transport() {
+        use std::sync::Arc;
+        use Client;
+        use config::Config;
+
+        let transport = Arc::new(StaticTransport {
+            status: 404,
+            body: "{\"message\":\"no such secret\"}",
+            requests: Mutex::new(Vec::new())
+        });
+        let client = Client::with_transport(Config::default(), transport);
+        match client.secrets().inspect(&String::from("missing")) {
+            Err(DockerError::NotFound { message }) => assert!(message == "no such secret"),
+            other => panic!("expected not found, got {:?}", other)
+        }
+    }
+}
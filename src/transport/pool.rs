@@ -0,0 +1,112 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+use curl::easy::Easy;
+
+// Idle handles kept around for reuse, anything above is dropped
+const MAX_IDLE: usize = 8;
+
+// Pool of curl handles shared by all threads using a `Client`. A
+// handle is checked out for the duration of one request, so
+// concurrent and nested requests each get their own connection.
+pub struct Pool {
+    idle: Mutex<Vec<Easy>>
+}
+
+pub struct PooledHandle<'a> {
+    pool: &'a Pool,
+    handle: Option<Easy>
+}
+
+impl Pool {
+    pub fn new() -> Pool {
+        Pool {
+            idle: Mutex::new(Vec::new())
+        }
+    }
+
+    pub fn checkout(&self) -> PooledHandle {
+        let handle = match self.idle.lock() {
+            Ok(mut idle) => idle.pop(),
+            Err(_) => None
+        };
+
+        PooledHandle {
+            pool: self,
+            handle: Some(handle.unwrap_or_else(Easy::new))
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.idle.lock().map(|idle| idle.len()).unwrap_or(0)
+    }
+
+    fn checkin(&self, handle: Easy) {
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < MAX_IDLE {
+                idle.push(handle);
+            }
+        }
+    }
+}
+
+impl<'a> Deref for PooledHandle<'a> {
+    type Target = Easy;
+
+    fn deref(&self) -> &Easy {
+        self.handle.as_ref().unwrap()
+    }
+}
+
+impl<'a> DerefMut for PooledHandle<'a> {
+    fn deref_mut(&mut self) -> &mut Easy {
+        self.handle.as_mut().unwrap()
+    }
+}
+
+impl<'a> Drop for PooledHandle<'a> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.pool.checkin(handle);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn handles_are_reused() {
+        use transport::pool::Pool;
+        let pool = Pool::new();
+        {
+            let _first = pool.checkout();
+            let _second = pool.checkout();
+            assert!(pool.idle() == 0);
+        }
+        assert!(pool.idle() == 2);
+        let _third = pool.checkout();
+        assert!(pool.idle() == 1);
+    }
+
+    #[test]
+    fn client_is_send_and_sync() {
+        use Client;
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Client>();
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Fixture files hold the exchanges between the client and a daemon
+// so endpoint clients can be tested offline:
+//
+//     let client = Client::builder().record("fixtures/swarm.json").build()?;
+//     client.swarm().get()?;
+//
+//     let client = Client::builder().replay("fixtures/swarm.json").build()?;
+//     client.swarm().get()?;   // served from the file
+//
+// Request headers are left out of the fixtures, they may carry
+// credentials. Streamed responses are recorded once their body has
+// been read to the end, hijacked connections aren't recorded.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json;
+
+use error::DockerError;
+use super::{HijackedStream, Request, Response, StreamingResponse, Transport};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub body: Option<Body>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedResponse {
+    pub status: u32,
+    pub headers: Vec<(String, String)>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub body: Option<Body>
+}
+
+// Text bodies are kept readable, anything else as raw bytes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Body {
+    Text(String),
+    Binary(Vec<u8>)
+}
+
+impl Body {
+    fn from_bytes(bytes: &[u8]) -> Option<Body> {
+        if bytes.is_empty() {
+            return None;
+        }
+
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(text) => Some(Body::Text(text)),
+            Err(error) => Some(Body::Binary(error.into_bytes()))
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Body::Text(text) => text.into_bytes(),
+            Body::Binary(bytes) => bytes
+        }
+    }
+}
+
+impl RecordedRequest {
+    fn from(request: &Request) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method.clone(),
+            path: request.path.clone(),
+            query: request.query.clone(),
+            body: request.body.as_ref().and_then(|body| Body::from_bytes(body))
+        }
+    }
+}
+
+fn io_error(path: &Path, error: ::std::io::Error) -> DockerError {
+    DockerError::Docker(format!("fixture {}: {}", path.display(), error))
+}
+
+pub fn read_fixture(path: &Path) -> Result<Vec<Exchange>, DockerError> {
+    let file = File::open(path).map_err(|error| io_error(path, error))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|error| DockerError::Docker(format!("fixture {}: {}", path.display(), error)))
+}
+
+pub fn write_fixture(path: &Path, exchanges: &[Exchange]) -> Result<(), DockerError> {
+    let file = File::create(path).map_err(|error| io_error(path, error))?;
+    serde_json::to_writer_pretty(&mut BufWriter::new(file), &exchanges)
+        .map_err(|error| DockerError::Docker(format!("fixture {}: {}", path.display(), error)))
+}
+
+// A fixture file being recorded. The file is created with the first
+// exchange and every further one replaces the closing bracket of the
+// array, so it stays a valid fixture after each exchange.
+struct Fixture {
+    path: PathBuf,
+    file: Mutex<Option<File>>
+}
+
+impl Fixture {
+    fn append(&self, exchange: &Exchange) -> Result<(), DockerError> {
+        let mut file = self.file.lock().unwrap();
+        let mut entry = Vec::new();
+        if file.is_none() {
+            *file = Some(File::create(&self.path).map_err(|error| io_error(&self.path, error))?);
+            entry.extend_from_slice(b"[\n");
+        } else {
+            entry.extend_from_slice(b",\n");
+        }
+        serde_json::to_writer_pretty(&mut entry, exchange)
+            .map_err(|error| DockerError::Docker(format!("fixture {}: {}", self.path.display(), error)))?;
+        entry.extend_from_slice(b"\n]\n");
+
+        let file = file.as_mut().unwrap();
+        let written = file.metadata().and_then(|metadata| {
+            if metadata.len() > 0 {
+                // back over the closing `\n]\n`
+                file.seek(SeekFrom::End(-3))?;
+            }
+            file.write_all(&entry)?;
+            file.flush()
+        });
+        written.map_err(|error| io_error(&self.path, error))
+    }
+}
+
+// Passes requests on to another transport and appends every exchange
+// to the fixture file as it happens
+pub struct RecordingTransport {
+    inner: Arc<Transport>,
+    fixture: Arc<Fixture>
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<Transport>, path: &Path) -> RecordingTransport {
+        RecordingTransport {
+            inner: inner,
+            fixture: Arc::new(Fixture {
+                path: path.to_path_buf(),
+                file: Mutex::new(None)
+            })
+        }
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn request(&self, request: &Request) -> Result<Response, DockerError> {
+        let response = self.inner.request(request)?;
+
+        self.fixture.append(&Exchange {
+            request: RecordedRequest::from(request),
+            response: RecordedResponse {
+                status: response.status,
+                headers: response.headers.clone(),
+                body: Body::from_bytes(&response.body)
+            }
+        })?;
+
+        Ok(response)
+    }
+
+    fn stream(&self, request: &Request) -> Result<StreamingResponse, DockerError> {
+        let response = self.inner.stream(request)?;
+        let exchange = Exchange {
+            request: RecordedRequest::from(request),
+            response: RecordedResponse {
+                status: response.status,
+                headers: response.headers.clone(),
+                body: None
+            }
+        };
+
+        Ok(StreamingResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: Box::new(RecordingReader {
+                inner: response,
+                fixture: self.fixture.clone(),
+                pending: Some(exchange),
+                body: Vec::new()
+            })
+        })
+    }
+
+    // Raw connections carry traffic both ways which doesn't fit into
+    // an exchange, they are passed through as they are
+    fn hijack(&self, request: &Request) -> Result<HijackedStream, DockerError> {
+        self.inner.hijack(request)
+    }
+}
+
+// Keeps a copy of a streamed body and records the exchange once the
+// body has ended. Streams dropped before their end aren't recorded.
+struct RecordingReader {
+    inner: StreamingResponse,
+    fixture: Arc<Fixture>,
+    // the exchange without its body
+    pending: Option<Exchange>,
+    body: Vec<u8>
+}
+
+impl Read for RecordingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        if count > 0 {
+            self.body.extend_from_slice(&buf[..count]);
+        } else if let Some(mut exchange) = self.pending.take() {
+            exchange.response.body = Body::from_bytes(&self.body);
+            self.fixture.append(&exchange).map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        }
+        Ok(count)
+    }
+}
+
+// Serves the exchanges of a fixture file. A request is answered by
+// the first exchange not used yet with the same method, path, query
+// and body, so repeated requests replay in recorded order.
+pub struct ReplayTransport {
+    exchanges: Mutex<Vec<Option<Exchange>>>
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<Exchange>) -> ReplayTransport {
+        ReplayTransport {
+            exchanges: Mutex::new(exchanges.into_iter().map(Some).collect())
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<ReplayTransport, DockerError> {
+        Ok(ReplayTransport::new(read_fixture(path)?))
+    }
+
+    // Exchanges that have not been asked for yet
+    pub fn remaining(&self) -> usize {
+        self.exchanges.lock().unwrap().iter().filter(|exchange| exchange.is_some()).count()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn request(&self, request: &Request) -> Result<Response, DockerError> {
+        let recorded = RecordedRequest::from(request);
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let slot = exchanges.iter_mut().find(|slot| {
+            slot.as_ref().map_or(false, |exchange| exchange.request == recorded)
+        });
+
+        match slot.and_then(|slot| slot.take()) {
+            Some(exchange) => Ok(Response {
+                status: exchange.response.status,
+                headers: exchange.response.headers,
+                body: exchange.response.body.map(Body::into_bytes).unwrap_or(Vec::new())
+            }),
+            None => Err(DockerError::Docker(format!("no recorded response for {} {}",
+                                                    request.method, request.path_and_query())))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn record_and_replay() {
+        use std::env;
+        use std::fs;
+        use mock::MockDaemon;
+
+        let path = env::temp_dir().join(format!("docker-rust-fixture-{}.json", ::std::process::id()));
+        {
+            let daemon = MockDaemon::start();
+            daemon.add_container(&json!({"Id": "abc", "Names": ["/web"]}));
+            let client = daemon.client_builder().record(&path).build().unwrap();
+            assert!(client.containers().all().unwrap().len() == 1);
+            assert!(client.swarm().get().is_err());
+        }
+
+        // the daemon is gone, everything comes out of the fixture
+        let client = ::Client::builder().replay(&path).build().unwrap();
+        assert!(client.containers().all().unwrap()[0].id == Some(String::from("abc")));
+        assert!(client.swarm().get().is_err());
+        assert!(client.containers().all().is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn record_streams() {
+        use std::env;
+        use std::fs;
+        use std::io::{Read, Write};
+        use mock::MockDaemon;
+        use transport::record::read_fixture;
+
+        let path = env::temp_dir().join(format!("docker-rust-stream-fixture-{}.json", ::std::process::id()));
+        {
+            let daemon = MockDaemon::start();
+            daemon.add_container(&json!({"Id": "abc"}));
+            daemon.respond("GET", "/containers/abc/export", 200, "not really a tar");
+            let client = daemon.client_builder().record(&path).build().unwrap();
+
+            let mut export = String::new();
+            client.containers().export("abc").unwrap().read_to_string(&mut export).unwrap();
+            assert!(read_fixture(&path).unwrap().len() == 1);
+
+            // goes through unrecorded
+            let mut attached = client.containers().attach("abc").unwrap();
+            attached.write_all(b"hi").unwrap();
+            attached.input.close().unwrap();
+            let mut echoed = String::new();
+            attached.read_to_string(&mut echoed).unwrap();
+            assert!(echoed == "hi");
+
+            assert!(client.containers().all().unwrap().len() == 1);
+            assert!(read_fixture(&path).unwrap().len() == 2);
+        }
+
+        let client = ::Client::builder().replay(&path).build().unwrap();
+        let mut export = String::new();
+        client.containers().export("abc").unwrap().read_to_string(&mut export).unwrap();
+        assert!(export == "not really a tar");
+        assert!(client.containers().all().unwrap().len() == 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn binary_bodies() {
+        use transport::record::Body;
+        assert!(Body::from_bytes(b"") == None);
+        assert!(Body::from_bytes(b"ok") == Some(Body::Text(String::from("ok"))));
+        assert!(Body::from_bytes(&[0xff, 0x00]) == Some(Body::Binary(vec![0xff, 0x00])));
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Reaches daemons through `ssh <host> docker system dial-stdio`, which
+// connects its stdin and stdout to the remote daemon socket. HTTP is
+// spoken over the pipes with the helpers from `http`, every process
+// is one keep-alive connection. The connect timeout goes to ssh as
+// `ConnectTimeout`, the read timeout is kept by polling its stdout.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use libc;
+
+use config::{has_port, port, Config};
+use error::DockerError;
+use http::{self, BodyReader, Head};
+use super::{Request, Response, StreamingResponse, Transport};
+use super::hijack::{handshake, CloseWrite, HijackedStream};
+
+// Idle connections kept around for later requests
+const MAX_IDLE: usize = 4;
+
+// Waits for the process in the background once the connection is gone,
+// closing stdin makes `dial-stdio` exit on its own
+struct Reaper(Option<Child>);
+
+impl Drop for Reaper {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.0.take() {
+            thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+    }
+}
+
+// Stdout of the process, gives up when nothing arrives for `timeout`
+struct Output {
+    pipe: ChildStdout,
+    timeout: Option<Duration>
+}
+
+impl Read for Output {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(timeout) = self.timeout {
+            wait_readable(&self.pipe, timeout)?;
+        }
+        self.pipe.read(buf)
+    }
+}
+
+fn wait_readable(pipe: &ChildStdout, timeout: Duration) -> io::Result<()> {
+    let millis = timeout.as_secs() * 1000 + timeout.subsec_millis() as u64;
+    let millis = if millis > libc::c_int::max_value() as u64 {
+        libc::c_int::max_value()
+    } else {
+        millis as libc::c_int
+    };
+    let mut fds = libc::pollfd { fd: pipe.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+    loop {
+        match unsafe { libc::poll(&mut fds, 1, millis) } {
+            0 => return Err(io::Error::new(io::ErrorKind::TimedOut, "no answer within the read timeout")),
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+            _ => return Ok(())
+        }
+    }
+}
+
+struct Connection {
+    input: Option<ChildStdin>,
+    output: BufReader<Output>,
+    // dropped last, after stdin has been closed
+    _process: Reaper
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.output.read(buf)
+    }
+}
+
+impl BufRead for Connection {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.output.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.output.consume(amount)
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.input {
+            Some(ref mut input) => input.write(buf),
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "input already closed"))
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.input {
+            Some(ref mut input) => input.flush(),
+            None => Ok(())
+        }
+    }
+}
+
+// Output of a hijacked connection, keeps the process around
+struct HijackedOutput {
+    output: BufReader<ChildStdout>,
+    _process: Reaper
+}
+
+impl Read for HijackedOutput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.output.read(buf)
+    }
+}
+
+struct HijackedInput(Option<ChildStdin>);
+
+impl Write for HijackedInput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.0 {
+            Some(ref mut input) => input.write(buf),
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "input already closed"))
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0 {
+            Some(ref mut input) => input.flush(),
+            None => Ok(())
+        }
+    }
+}
+
+impl CloseWrite for HijackedInput {
+    fn close_write(&mut self) -> io::Result<()> {
+        self.0.take();
+        Ok(())
+    }
+}
+
+pub struct SshTransport {
+    command: String,
+    destination: String,
+    port: Option<String>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    idle: Mutex<Vec<Connection>>
+}
+
+impl SshTransport {
+    // Runs the ssh command of `config` to reach `address`,
+    // [user@]host[:port] as in an ssh:// DOCKER_HOST
+    pub fn new(address: &str, config: &Config) -> SshTransport {
+        let (destination, port) = if has_port(address) {
+            let port = port(address);
+            (&address[..address.len() - port.len() - 1], Some(String::from(port)))
+        } else {
+            (address, None)
+        };
+
+        SshTransport {
+            command: config.ssh_command.clone(),
+            destination: String::from(destination),
+            port: port,
+            connect_timeout: config.connect_timeout,
+            read_timeout: config.read_timeout,
+            idle: Mutex::new(Vec::new())
+        }
+    }
+
+    fn spawn(&self) -> Result<(ChildStdin, ChildStdout, Reaper), DockerError> {
+        let mut command = Command::new(self.command.as_str());
+        if let Some(ref port) = self.port {
+            command.arg("-p").arg(port.as_str());
+        }
+        if let Some(timeout) = self.connect_timeout {
+            // whole seconds only, and 0 would mean no timeout
+            let seconds = timeout.as_secs() + if timeout.subsec_nanos() > 0 { 1 } else { 0 };
+            let seconds = if seconds == 0 { 1 } else { seconds };
+            command.arg("-o").arg(format!("ConnectTimeout={}", seconds));
+        }
+        let mut child = command
+            .arg("--")
+            .arg(self.destination.as_str())
+            .args(&["docker", "system", "dial-stdio"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| DockerError::Docker(format!("could not run {}: {}", self.command, error)))?;
+
+        match (child.stdin.take(), child.stdout.take()) {
+            (Some(input), Some(output)) => Ok((input, output, Reaper(Some(child)))),
+            _ => Err(DockerError::Docker(format!("{} has no stdin or stdout", self.command)))
+        }
+    }
+
+    fn connect(&self) -> Result<Connection, DockerError> {
+        let (input, output, process) = self.spawn()?;
+        Ok(Connection {
+            input: Some(input),
+            output: BufReader::new(Output { pipe: output, timeout: None }),
+            _process: process
+        })
+    }
+
+    // Writes `request` and reads the head of the response, on an idle
+    // connection if there is one. `timeout` stays on for the body.
+    fn send(&self, request: &Request, timeout: Option<Duration>) -> Result<(Connection, Head), DockerError> {
+        let idle = self.idle.lock().unwrap().pop();
+        if let Some(mut connection) = idle {
+            connection.output.get_mut().timeout = timeout;
+            // the remote end may have given up on idle connections. A
+            // request that couldn't be written goes out again on a new
+            // connection, one that was written only if running it twice
+            // does no harm.
+            if write_request(&mut connection, request).is_ok() {
+                match read_response_head(&mut connection) {
+                    Ok(Some(head)) => return Ok((connection, head)),
+                    Ok(None) if !idempotent(request) => return Err(self.closed()),
+                    Err(error) if !idempotent(request) => return Err(DockerError::from(error)),
+                    _ => ()
+                }
+            }
+        }
+
+        let mut connection = self.connect()?;
+        connection.output.get_mut().timeout = timeout;
+        write_request(&mut connection, request)?;
+        match read_response_head(&mut connection)? {
+            Some(head) => Ok((connection, head)),
+            None => Err(self.closed())
+        }
+    }
+
+    fn closed(&self) -> DockerError {
+        DockerError::Docker(format!("{} closed the connection", self.command))
+    }
+
+    fn release(&self, connection: Connection) {
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < MAX_IDLE {
+            idle.push(connection);
+        }
+    }
+}
+
+fn write_request(connection: &mut Connection, request: &Request) -> io::Result<()> {
+    let start = format!("{} {} HTTP/1.1", request.method, request.path_and_query());
+    let mut headers = vec![(String::from("Host"), String::from("docker"))];
+    headers.extend(request.headers.iter().cloned());
+    let body = match request.body {
+        Some(ref body) => body.as_slice(),
+        None => &[]
+    };
+    http::write_message(connection, start.as_str(), &headers, body)
+}
+
+fn read_response_head(connection: &mut Connection) -> io::Result<Option<Head>> {
+    loop {
+        match http::read_head(connection)? {
+            // interim answers like `100 Continue`
+            Some(ref head) if status(head) / 100 == 1 => (),
+            head => return Ok(head)
+        }
+    }
+}
+
+// Requests that can be sent twice without changing anything
+fn idempotent(request: &Request) -> bool {
+    request.method == "GET" || request.method == "HEAD"
+}
+
+fn status(head: &Head) -> u32 {
+    head.start.split_whitespace().nth(1).and_then(|code| code.parse().ok()).unwrap_or(0)
+}
+
+// Whether the body is read until the connection closes, which also
+// means it can't be used again
+fn until_eof(head: &Head) -> bool {
+    head.content_length().is_none() && !head.is_chunked()
+}
+
+fn has_body(request: &Request, head: &Head) -> bool {
+    let status = status(head);
+    request.method != "HEAD" && status != 204 && status != 304
+}
+
+fn closes(head: &Head) -> bool {
+    head.header("Connection").map_or(false, |value| value.eq_ignore_ascii_case("close"))
+}
+
+impl Transport for SshTransport {
+    fn request(&self, request: &Request) -> Result<Response, DockerError> {
+        let (mut connection, head) = self.send(request, self.read_timeout)?;
+        let body = if has_body(request, &head) {
+            http::read_body(&mut connection, &head, true)?
+        } else {
+            Vec::new()
+        };
+
+        let reusable = !closes(&head) && (!has_body(request, &head) || !until_eof(&head));
+        if reusable {
+            self.release(connection);
+        }
+
+        Ok(Response {
+            status: status(&head),
+            headers: head.headers,
+            body: body
+        })
+    }
+
+    // The connection belongs to the response until it is dropped. No
+    // read timeout, streams may be quiet for as long as they like.
+    fn stream(&self, request: &Request) -> Result<StreamingResponse, DockerError> {
+        let (connection, head) = self.send(request, None)?;
+        let body : Box<Read + Send> = if has_body(request, &head) {
+            Box::new(BodyReader::new(connection, &head, true))
+        } else {
+            Box::new(io::empty())
+        };
+
+        Ok(StreamingResponse {
+            status: status(&head),
+            headers: head.headers,
+            body: body
+        })
+    }
+
+    fn hijack(&self, request: &Request) -> Result<HijackedStream, DockerError> {
+        let (input, output, process) = self.spawn()?;
+        let output = HijackedOutput {
+            output: BufReader::new(output),
+            _process: process
+        };
+        handshake(output, HijackedInput(Some(input)), request, "docker")
+    }
+}
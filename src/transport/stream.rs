@@ -0,0 +1,45 @@
+// Copyright 2017 Kai Strempel
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, Read};
+
+use serde::Deserialize;
+
+use common::JsonStream;
+use super::find_header;
+
+// Response whose body is read as it arrives, for endpoints that never
+// end (events, logs with follow, stats) or are too large to buffer
+pub struct StreamingResponse {
+    pub status: u32,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<Read + Send>
+}
+
+impl StreamingResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    // Decodes the json objects the body is made of
+    pub fn json_stream<T: Deserialize>(self) -> JsonStream<T> {
+        JsonStream::new(self)
+    }
+}
+
+impl Read for StreamingResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.body.read(buf)
+    }
+}
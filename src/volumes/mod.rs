@@ -33,11 +33,30 @@ impl<'a> VolumesClient<'a> {
 mod tests {
     #[test]
     fn it_works() {
-        use Client;
         use volumes::VolumesClient;
-        let client = Client::from_env();
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        daemon.add_volume(&json!({"Name": "data", "Driver": "local", "Mountpoint": "/var/lib/docker/volumes/data",
+                                  "Labels": {}, "Scope": "local", "Options": {}}));
+        let client = daemon.client();
         let volumes_client = VolumesClient::new(&client);
         let volumes = volumes_client.get();
         assert!(volumes.is_ok());
     }
+
+    #[test]
+    fn volumes_without_labels() {
+        use volumes::VolumesClient;
+        use mock::MockDaemon;
+        let daemon = MockDaemon::start();
+        // anonymous volumes come without labels and options
+        daemon.add_volume(&json!({"Name": "3f2a", "Driver": "local", "Mountpoint": "/var/lib/docker/volumes/3f2a",
+                                  "Labels": null, "Scope": "local", "Options": null,
+                                  "UsageData": {"Size": 5368709120i64, "RefCount": 1}}));
+        let client = daemon.client();
+        let volumes = VolumesClient::new(&client).get().unwrap();
+        assert!(volumes.warnings.is_none());
+        assert!(volumes.volumes[0].labels.is_none() && volumes.volumes[0].options.is_none());
+        assert!(volumes.volumes[0].usage_data.as_ref().unwrap().size == 5368709120);
+    }
 }
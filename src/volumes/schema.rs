@@ -20,7 +20,7 @@ pub struct VolumeUsageData {
 
    // The disk space used by the volume (local driver only)
    #[serde(rename = "Size")]
-   pub size : i32,
+   pub size : i64,
 
    // The number of containers referencing this volume.
    #[serde(rename = "RefCount")]
@@ -47,17 +47,19 @@ pub struct Volume {
    #[serde(skip_serializing_if = "Option::is_none")]
    pub status : Option<HashMap<String,String>>,
 
-   // User-defined key/value metadata.
+   // User-defined key/value metadata, null for volumes created without any.
    #[serde(rename = "Labels")]
-   pub labels : HashMap<String,String>,
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub labels : Option<HashMap<String,String>>,
 
    // The level at which the volume exists. Either `global` for cluster-wide, or `local` for machine level.
    #[serde(rename = "Scope")]
    pub scope : String,
 
-   // The driver specific options used when creating the volume.
+   // The driver specific options used when creating the volume, null when there are none.
    #[serde(rename = "Options")]
-   pub options : HashMap<String,String>,
+   #[serde(skip_serializing_if = "Option::is_none")]
+   pub options : Option<HashMap<String,String>>,
 
    #[serde(rename = "UsageData")]
    #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,7 +72,8 @@ pub struct Volumes {
     pub volumes : Vec<Volume>,
 
     #[serde(rename = "Warnings")]
-    pub warnings : Vec<String>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings : Option<Vec<String>>
 }
 
 // Optional configuration for the `tmpfs` type.
@@ -113,7 +116,7 @@ pub struct MountVolumeOptions {
    #[serde(skip_serializing_if = "Option::is_none")]
    pub no_copy : Option<bool>,
 
-   // User-defined key/value metadata.
+   // User-defined key/value metadata, null for volumes created without any.
    #[serde(rename = "Labels")]
    #[serde(skip_serializing_if = "Option::is_none")]
    pub labels : Option<HashMap<String,String>>,
@@ -0,0 +1,121 @@
+extern crate docker;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use docker::{DockerError, RegistryAuth};
+use docker::auth::{AuthEntry, ConfigFile, CredentialHelper, DOCKER_HUB};
+
+// Stand-in for `docker-credential-test`, knows two registries and
+// writes whatever it is asked to store or erase next to itself
+const HELPER: &'static str = r#"#!/bin/sh
+dir=$(dirname "$0")
+case "$1" in
+    get)
+        read server
+        case "$server" in
+            registry.example.com)
+                echo '{"ServerURL":"registry.example.com","Username":"joe","Secret":"s3cret"}' ;;
+            https://private.example.com)
+                echo '{"ServerURL":"https://private.example.com","Username":"ann","Secret":"pw"}' ;;
+            https://index.docker.io/v1/)
+                echo '{"ServerURL":"https://index.docker.io/v1/","Username":"<token>","Secret":"hub-token"}' ;;
+            *)
+                echo "credentials not found in native keychain"
+                exit 1 ;;
+        esac ;;
+    store) cat > "$dir/stored" ;;
+    erase) cat > "$dir/erased" ;;
+    list) echo '{"registry.example.com":"joe"}' ;;
+    *)
+        echo "unknown action $1" >&2
+        exit 1 ;;
+esac
+"#;
+
+// Fails right away without reading its input, like a helper for a
+// locked keychain
+const BROKEN_HELPER: &'static str = r#"#!/bin/sh
+echo "keychain is locked" >&2
+exit 3
+"#;
+
+fn install_helper() -> PathBuf {
+    let dir = env::temp_dir().join(format!("docker-rust-helpers-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for &(name, script) in [("docker-credential-test", HELPER), ("docker-credential-broken", BROKEN_HELPER)].iter() {
+        let program = dir.join(name);
+        File::create(&program).unwrap().write_all(script.as_bytes()).unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let path = env::var("PATH").unwrap_or_default();
+    env::set_var("PATH", format!("{}:{}", dir.display(), path));
+    dir
+}
+
+fn read(path: PathBuf) -> String {
+    let mut text = String::new();
+    File::open(path).unwrap().read_to_string(&mut text).unwrap();
+    text
+}
+
+#[test]
+fn credential_helpers() {
+    let dir = install_helper();
+    let helper = CredentialHelper::new("test");
+
+    let auth = helper.get("registry.example.com").unwrap().unwrap();
+    assert!(auth == RegistryAuth::new("joe", "s3cret").server_address("registry.example.com"));
+    assert!(helper.get("quay.io").unwrap().is_none());
+    assert!(helper.list().unwrap().get("registry.example.com") == Some(&String::from("joe")));
+
+    helper.store(&RegistryAuth::new("ann", "pw").server_address("quay.io")).unwrap();
+    assert!(read(dir.join("stored")) == r#"{"ServerURL":"quay.io","Username":"ann","Secret":"pw"}"#);
+    helper.erase("quay.io").unwrap();
+    assert!(read(dir.join("erased")) == "quay.io");
+
+    assert!(CredentialHelper::new("missing").get("quay.io").is_err());
+
+    // more input than a pipe holds, the helper is gone before it is written
+    let broken = CredentialHelper::new("broken");
+    let secret = vec!["x"; 1 << 20].concat();
+    match broken.store(&RegistryAuth::new("ann", secret.as_str()).server_address("quay.io")) {
+        Err(DockerError::Docker(ref message)) => {
+            assert!(message.contains("exit status: 3"));
+            assert!(message.ends_with("keychain is locked"));
+        }
+        other => panic!("expected the helper's failure, got {:?}", other)
+    }
+    match broken.list() {
+        Err(DockerError::Docker(ref message)) => assert!(message.ends_with("keychain is locked")),
+        other => panic!("expected the helper's failure, got {:?}", other)
+    }
+
+    // the config file sends every registry through the store, except
+    // for those with a helper of their own
+    let config = ConfigFile {
+        creds_store: Some(String::from("test")),
+        cred_helpers: vec![(String::from("quay.io"), String::from("missing"))].into_iter().collect(),
+        ..ConfigFile::default()
+    };
+    let hub = config.image_auth("alpine:latest").unwrap().unwrap();
+    assert!(hub.identity_token == Some(String::from("hub-token")));
+    assert!(hub.server_address == Some(String::from(DOCKER_HUB)));
+    assert!(config.auth("https://registry.example.com/v2/").unwrap().unwrap().username == Some(String::from("joe")));
+    assert!(config.auth("quay.io").is_err());
+
+    // `docker login https://private.example.com` stores the address
+    // with its scheme, the key in `auths` tells which one it was
+    let config = ConfigFile {
+        auths: vec![(String::from("https://private.example.com"), AuthEntry::default())].into_iter().collect(),
+        creds_store: Some(String::from("test")),
+        ..ConfigFile::default()
+    };
+    assert!(config.image_auth("private.example.com/app").unwrap().unwrap().username == Some(String::from("ann")));
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,56 @@
+extern crate docker;
+
+use docker::{Client, DockerError};
+
+fn replay(fixture: &str) -> Client {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture);
+    Client::builder().replay(path).build().unwrap()
+}
+
+#[test]
+fn containers_all(){
+    let client = replay("containers_all.json");
+    let containers = client.containers().all().unwrap();
+
+    assert!(containers.len() == 1);
+    assert!(containers[0].names == Some(vec![String::from("/boring_feynman")]));
+    assert!(containers[0].ports.as_ref().unwrap()[0].public_port == Some(3333));
+}
+
+#[test]
+fn swarm_get(){
+    let client = replay("swarm_get.json");
+    let swarm = client.swarm().get().unwrap();
+
+    assert!(swarm.id == "abajmipo7b4xz5ip2nrla6b11");
+    assert!(swarm.spec.name == Some(String::from("default")));
+}
+
+#[test]
+fn containers_inspect(){
+    let client = replay("containers_inspect.json");
+    let container = client.containers().inspect("db", false).unwrap();
+
+    assert!(container.name == Some(String::from("/db")));
+    assert!(container.restart_count == Some(2));
+    let state = container.state.unwrap();
+    assert!(state.oom_killed == Some(true));
+    assert!(state.exit_code == Some(137));
+    let health = state.health.unwrap();
+    assert!(health.status == Some(String::from("unhealthy")));
+    assert!(health.log.unwrap()[1].output == Some(String::from("no response\n")));
+    let ports = container.network_settings.unwrap().ports.unwrap();
+    assert!(ports["5432/tcp"].as_ref().unwrap()[0].host_port == Some(String::from("5433")));
+    assert!(ports["8080/tcp"].is_none());
+    assert!(container.mounts.unwrap()[1].name == Some(String::from("3c1d0a9c5b36")));
+}
+
+#[test]
+fn schema_drift(){
+    let client = replay("containers_all_drift.json");
+
+    match client.containers().all() {
+        Err(DockerError::Json { payload, .. }) => assert!(payload.contains("2222/tcp")),
+        other => panic!("expected a decode error, got {:?}", other)
+    }
+}
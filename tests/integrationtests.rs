@@ -1,10 +1,25 @@
 extern crate docker;
+#[macro_use]
+extern crate serde_json;
 
-use docker::Client;
+use docker::mock::MockDaemon;
+
+fn daemon() -> MockDaemon {
+    let daemon = MockDaemon::start();
+    daemon.add_image(&json!({"Id": "sha256:1"}));
+    daemon.add_image(&json!({"Id": "sha256:2"}));
+    daemon.add_container(&json!({"Id": "abc"}));
+    daemon.add_container(&json!({"Id": "def"}));
+    daemon.add_network(&json!({"Name": "bridge"}));
+    daemon.add_network(&json!({"Name": "host"}));
+    daemon.set_swarm(&json!({"ID": "swarm1", "Spec": {}, "JoinTokens": {}}));
+    daemon
+}
 
 #[test]
 fn get_all(){
-    let client = Client::from_env();
+    let daemon = daemon();
+    let client = daemon.client();
 
     assert!(client.images().all().unwrap().len()>1);
     assert!(client.containers().all().unwrap().len()>1);
@@ -13,4 +28,24 @@ fn get_all(){
     assert!(client.volumes().get().is_ok());
     assert!(client.tasks().get().is_ok());
     assert!(client.secrets().all().unwrap().len()==0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn shared_between_threads(){
+    use std::sync::Arc;
+    use std::thread;
+
+    let daemon = daemon();
+    let client = Arc::new(daemon.client());
+    let workers : Vec<_> = (0..4).map(|_| {
+        let client = client.clone();
+        thread::spawn(move || {
+            assert!(client.images().all().is_ok());
+            assert!(client.containers().all().is_ok());
+        })
+    }).collect();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+}
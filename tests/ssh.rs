@@ -0,0 +1,177 @@
+extern crate docker;
+#[macro_use]
+extern crate serde_json;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use docker::ClientBuilder;
+use docker::mock::MockDaemon;
+
+// Stand-in for ssh that connects its stdin and stdout to the mock
+// daemon like `docker system dial-stdio` would, and notes its arguments
+const FAKE_SSH: &'static str = r#"#!/usr/bin/perl
+use IO::Socket::UNIX;
+use File::Basename;
+
+open(my $args, '>>', dirname($0) . "/args") or die;
+print $args join(' ', @ARGV) . "\n";
+close($args);
+
+my $socket = IO::Socket::UNIX->new(Peer => $ENV{'MOCK_SOCKET'}) or die "connect: $!";
+if (my $pid = fork()) {
+    while (sysread(STDIN, my $data, 65536)) {
+        syswrite($socket, $data);
+    }
+    shutdown($socket, 1);
+    waitpid($pid, 0);
+} else {
+    while (sysread($socket, my $data, 65536)) {
+        syswrite(STDOUT, $data);
+    }
+    exit 0;
+}
+"#;
+
+struct FakeSsh(PathBuf);
+
+impl FakeSsh {
+    fn install(daemon: &MockDaemon) -> Option<FakeSsh> {
+        let perl = Command::new("perl").args(&["-MIO::Socket::UNIX", "-e", "1"]).status();
+        if !perl.map(|status| status.success()).unwrap_or(false) {
+            return None;
+        }
+
+        let dir = env::temp_dir().join(format!("docker-rust-ssh-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("ssh");
+        File::create(&program).unwrap().write_all(FAKE_SSH.as_bytes()).unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        env::set_var("MOCK_SOCKET", daemon.socket_path());
+        Some(FakeSsh(dir))
+    }
+
+    fn program(&self) -> String {
+        String::from(self.0.join("ssh").to_str().unwrap())
+    }
+
+    fn calls(&self) -> Vec<String> {
+        let mut text = String::new();
+        File::open(self.0.join("args")).unwrap().read_to_string(&mut text).unwrap();
+        text.lines().map(String::from).collect()
+    }
+}
+
+impl Drop for FakeSsh {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn ssh_transport() {
+    let daemon = MockDaemon::start();
+    daemon.add_container(&json!({"Id": "abc", "Names": ["/web"]}));
+    daemon.add_image(&json!({"Id": "sha256:1"}));
+    daemon.add_event(&json!({"Type": "container", "Action": "start"}));
+    let ssh = match FakeSsh::install(&daemon) {
+        Some(ssh) => ssh,
+        None => return
+    };
+
+    let client = ClientBuilder::new()
+        .host("ssh://builder@build-host:2222")
+        .ssh_command(ssh.program().as_str())
+        .build()
+        .unwrap();
+
+    // both requests share one connection
+    assert!(client.images().all().unwrap().len() == 1);
+    assert!(client.containers().all().unwrap().len() == 1);
+    assert!(client.system().ping().unwrap() == "OK");
+    assert!(ssh.calls() == vec!["-p 2222 -- builder@build-host docker system dial-stdio"]);
+
+    let event = client.system().events().unwrap().next().unwrap().unwrap();
+    assert!(event.action == Some(String::from("start")));
+
+    let mut stream = client.containers().attach("abc").unwrap();
+    stream.write_all(b"over ssh").unwrap();
+    stream.input.close().unwrap();
+    let mut output = String::new();
+    stream.read_to_string(&mut output).unwrap();
+    assert!(output == "over ssh");
+
+    assert!(client.containers().export("missing").is_err());
+    assert!(daemon.requests().len() == 6);
+}
+
+// Answers one request and hangs up on the next, like a daemon that
+// dropped the idle connection after reading the request
+const ONE_SHOT_SSH: &'static str = r#"#!/bin/sh
+echo call >> "$(dirname "$0")/args"
+cr=$(printf '\r')
+while read -r line && [ "$line" != "$cr" ]; do :; done
+printf 'HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK'
+read -r line
+"#;
+
+#[test]
+fn retries_only_idempotent_requests() {
+    let dir = env::temp_dir().join(format!("docker-rust-ssh-retry-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("ssh");
+    File::create(&program).unwrap().write_all(ONE_SHOT_SSH.as_bytes()).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    let ssh = FakeSsh(dir);
+
+    let client = ClientBuilder::new()
+        .host("ssh://build-host")
+        .ssh_command(ssh.program().as_str())
+        .build()
+        .unwrap();
+
+    assert!(client.system().ping().unwrap() == "OK");
+    // sent again on a new connection
+    assert!(client.system().ping().unwrap() == "OK");
+    assert!(ssh.calls().len() == 2);
+
+    // may have been acted on, so it isn't sent again
+    assert!(client.containers().start("abc").is_err());
+    assert!(ssh.calls().len() == 2);
+}
+
+// Reads the request and never answers
+const SILENT_SSH: &'static str = r#"#!/bin/sh
+echo "$@" >> "$(dirname "$0")/args"
+cr=$(printf '\r')
+while read -r line && [ "$line" != "$cr" ]; do :; done
+sleep 10
+"#;
+
+#[test]
+fn timeouts() {
+    use std::time::{Duration, Instant};
+    let dir = env::temp_dir().join(format!("docker-rust-ssh-timeouts-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("ssh");
+    File::create(&program).unwrap().write_all(SILENT_SSH.as_bytes()).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    let ssh = FakeSsh(dir);
+
+    let client = ClientBuilder::new()
+        .host("ssh://build-host")
+        .ssh_command(ssh.program().as_str())
+        .connect_timeout(Duration::from_millis(2500))
+        .read_timeout(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    assert!(client.system().ping().is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(ssh.calls() == vec!["-o ConnectTimeout=3 -- build-host docker system dial-stdio"]);
+}
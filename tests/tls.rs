@@ -0,0 +1,158 @@
+extern crate docker;
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use docker::{Client, Config, TlsConfig};
+
+// Self-signed stand-in for a remote daemon, `openssl s_server -WWW`
+// serves files below its working directory over mutual TLS. Without
+// `-WWW` it connects the TLS connection to its stdin and stdout.
+struct TlsServer {
+    dir: PathBuf,
+    port: u16,
+    process: Child
+}
+
+impl Drop for TlsServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn openssl(dir: &Path, args: &[&str]) -> bool {
+    Command::new("openssl").args(args).current_dir(dir)
+        .stdout(Stdio::null()).stderr(Stdio::null())
+        .status().map(|status| status.success()).unwrap_or(false)
+}
+
+fn start_server(name: &str, www: bool) -> Option<TlsServer> {
+    let dir = std::env::temp_dir().join(format!("docker-rust-tls-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("v1.27/images")).unwrap();
+    fs::File::create(dir.join("v1.27/images/json")).unwrap().write_all(b"[]").unwrap();
+    fs::File::create(dir.join("san.ext")).unwrap()
+        .write_all(b"subjectAltName=DNS:docker.test\n").unwrap();
+
+    let generated =
+        openssl(&dir, &["req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "1",
+                        "-subj", "/CN=test-ca", "-keyout", "ca-key.pem", "-out", "ca.pem"]) &&
+        openssl(&dir, &["req", "-newkey", "rsa:2048", "-nodes", "-subj", "/CN=docker.test",
+                        "-keyout", "server-key.pem", "-out", "server.csr"]) &&
+        openssl(&dir, &["x509", "-req", "-in", "server.csr", "-CA", "ca.pem", "-CAkey", "ca-key.pem",
+                        "-CAcreateserial", "-days", "1", "-extfile", "san.ext", "-out", "server.pem"]) &&
+        openssl(&dir, &["req", "-newkey", "rsa:2048", "-nodes", "-subj", "/CN=client",
+                        "-keyout", "key.pem", "-out", "client.csr"]) &&
+        openssl(&dir, &["x509", "-req", "-in", "client.csr", "-CA", "ca.pem", "-CAkey", "ca-key.pem",
+                        "-CAcreateserial", "-days", "1", "-out", "cert.pem"]);
+    if !generated {
+        let _ = fs::remove_dir_all(&dir);
+        return None;
+    }
+
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut command = Command::new("openssl");
+    command.args(&["s_server", "-quiet", "-accept", port.to_string().as_str(),
+                   "-cert", "server.pem", "-key", "server-key.pem",
+                   "-CAfile", "ca.pem", "-Verify", "1"])
+        .current_dir(&dir)
+        .stderr(Stdio::null());
+    if www {
+        command.arg("-WWW").stdin(Stdio::null()).stdout(Stdio::null());
+    } else {
+        // exits once the client is gone, which closes its stdout. The
+        // first connection is the probe below.
+        command.args(&["-naccept", "2"]).stdin(Stdio::piped()).stdout(Stdio::piped());
+    }
+    let process = command.spawn().unwrap();
+    let server = TlsServer { dir: dir, port: port, process: process };
+
+    for _ in 0..50 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return Some(server);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    None
+}
+
+#[test]
+fn mutual_tls_with_server_name() {
+    let server = match start_server("mtls", true) {
+        Some(server) => server,
+        None => return
+    };
+
+    let tls = TlsConfig::from_cert_path(&server.dir, true).server_name("docker.test");
+    let client = Client::with_config(Config::tcp(&format!("127.0.0.1:{}", server.port), Some(tls)));
+    assert!(client.images().all().unwrap().len() == 0);
+}
+
+#[test]
+fn rejects_wrong_hostname() {
+    let server = match start_server("hostname", true) {
+        Some(server) => server,
+        None => return
+    };
+
+    let tls = TlsConfig::from_cert_path(&server.dir, true);
+    let client = Client::with_config(Config::tcp(&format!("127.0.0.1:{}", server.port), Some(tls.clone())));
+    assert!(client.images().all().is_err());
+
+    let client = Client::with_config(Config::tcp(&format!("127.0.0.1:{}", server.port),
+                                                 Some(tls.verify_hostname(false))));
+    assert!(client.images().all().is_ok());
+}
+
+#[test]
+fn hijack_over_tls() {
+    let mut server = match start_server("hijack", false) {
+        Some(server) => server,
+        None => return
+    };
+
+    let tls = TlsConfig::from_cert_path(&server.dir, true).server_name("docker.test");
+    let client = Client::with_config(Config::tcp(&format!("127.0.0.1:{}", server.port), Some(tls)));
+    let attach = thread::spawn(move || client.containers().attach("abc"));
+
+    // play the daemon: read the request and agree to the upgrade
+    let mut request = BufReader::new(server.process.stdout.take().unwrap());
+    let mut line = String::new();
+    request.read_line(&mut line).unwrap();
+    assert!(line.starts_with("POST /v1.27/containers/abc/attach?stream=1"));
+    while line.trim() != "" {
+        line.clear();
+        request.read_line(&mut line).unwrap();
+    }
+    let mut daemon = server.process.stdin.take().unwrap();
+    daemon.write_all(b"HTTP/1.1 101 UPGRADED\r\nUpgrade: tcp\r\n\r\nhello").unwrap();
+    daemon.flush().unwrap();
+
+    let mut stream = attach.join().unwrap().unwrap();
+    let mut greeting = [0u8; 5];
+    stream.read_exact(&mut greeting).unwrap();
+    assert!(&greeting == b"hello");
+
+    stream.write_all(b"ping").unwrap();
+    let mut input = [0u8; 4];
+    request.read_exact(&mut input).unwrap();
+    assert!(&input == b"ping");
+
+    // the end of stdin reaches the daemon
+    stream.input.close().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut rest = Vec::new();
+        let _ = sender.send(request.read_to_end(&mut rest).map(|_| rest));
+    });
+    let rest = receiver.recv_timeout(Duration::from_secs(10)).expect("stdin was not closed").unwrap();
+    assert!(rest.is_empty());
+}